@@ -0,0 +1,48 @@
+//! Trains an n-tuple network by TD(0) self-play and saves its weights.
+//!
+//! Usage: `cargo run --release --example train_ntuple -- <games> <weights-file> [seed]`
+//! Training resumes from the weights file if it already exists.
+
+use std::env;
+use std::io::ErrorKind;
+use std::process::ExitCode;
+
+use library_of_2048::ntuple::DEFAULT_LEARNING_RATE;
+use library_of_2048::{NTupleNetwork, SpawnModel};
+
+// Games between progress reports and checkpoints.
+const REPORT_EVERY: usize = 1000;
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let (Some(games), Some(path)) = (args.first().and_then(|g| g.parse::<usize>().ok()), args.get(1)) else {
+        eprintln!("usage: train_ntuple <games> <weights-file> [seed]");
+        return ExitCode::FAILURE;
+    };
+    let seed = args.get(2).and_then(|s| s.parse().ok()).unwrap_or_else(rand::random);
+    let mut spawns = SpawnModel::classic(seed);
+    println!("spawn seed {}", seed);
+    let mut network = match NTupleNetwork::load(path) {
+        Ok(network) => network,
+        Err(e) if e.kind() == ErrorKind::NotFound => NTupleNetwork::default(),
+        Err(e) => {
+            eprintln!("failed to load {}: {}", path, e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let mut played = 0;
+    while played < games {
+        let batch = REPORT_EVERY.min(games - played);
+        let scores = network.train(batch, DEFAULT_LEARNING_RATE, &mut spawns);
+        played += batch;
+        let mean = scores.iter().sum::<u64>() as f64 / batch as f64;
+        let best = scores.iter().max().copied().unwrap_or(0);
+        println!("{played:>8} games  mean score {mean:>10.1}  best {best}");
+        if let Err(e) = network.save(path) {
+            eprintln!("failed to save {}: {}", path, e);
+            return ExitCode::FAILURE;
+        }
+    }
+    ExitCode::SUCCESS
+}
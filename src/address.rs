@@ -0,0 +1,195 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::board::{Board, extract_proto_and_tiles, fill_board};
+use crate::local_id::{Alphabet, LocalId, encode_base11};
+use crate::protoboards::{GlobalId, MIN_T, Protoboard, id_range, max_t};
+use crate::size::BoardSize;
+
+/// Reasons a textual board address (or one of its parts) can be rejected.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AddressError {
+    Malformed(String),
+    InvalidSize(String),
+    NonIntegerT,
+    TOutOfRange { t: u32, max: u32 },
+    NonIntegerGlobalId,
+    GlobalIdBelowRange { gid: usize, min: usize, t: u32 },
+    GlobalIdAboveRange { gid: usize, max: usize, t: u32 },
+    LocalIdLength { expected: usize, t: u32 },
+    LocalIdDigits(Alphabet),
+    InvalidLocalId(String),
+    InvalidTile(u32),
+}
+
+impl fmt::Display for AddressError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AddressError::Malformed(s) => {
+                write!(f, "Invalid address '{}'! Expected [RxC:]t:global:local, e.g. 2:119:AA", s)
+            }
+            AddressError::InvalidSize(e) => write!(f, "{}", e),
+            AddressError::NonIntegerT => write!(f, "Invalid t! Non-integer value."),
+            AddressError::TOutOfRange { t, max } => write!(f, "Invalid t! {} is outside {}..={}", t, MIN_T, max),
+            AddressError::NonIntegerGlobalId => write!(f, "Invalid ID! Non-integer value."),
+            AddressError::GlobalIdBelowRange { gid, min, t } => {
+                write!(f, "Invalid ID! {} is less than minimum {} in range for t={}", gid, min, t)
+            }
+            AddressError::GlobalIdAboveRange { gid, max, t } => {
+                write!(f, "Invalid ID! {} is greater than maximum {} in range for t={}", gid, max, t)
+            }
+            AddressError::LocalIdLength { expected, t } => {
+                write!(f, "Local ID must be exactly {} characters for t={}.", expected, t)
+            }
+            AddressError::LocalIdDigits(alphabet) => write!(f, "Local ID must only use digits {}.", alphabet.digits()),
+            AddressError::InvalidLocalId(e) => write!(f, "{}", e),
+            AddressError::InvalidTile(v) => write!(f, "Invalid tile {}! Tiles must be powers of two from 2.", v),
+        }
+    }
+}
+
+impl std::error::Error for AddressError {}
+
+pub fn validate_t(size: BoardSize, input: &str) -> Result<u32, AddressError> {
+    let t = input.trim().parse::<u32>().map_err(|_| AddressError::NonIntegerT)?;
+    if !(MIN_T..=max_t(size)).contains(&t) {
+        return Err(AddressError::TOutOfRange { t, max: max_t(size) });
+    }
+    Ok(t)
+}
+
+/// Parses a global ID and checks that it lies in the ID range for t.
+pub fn validate_global_id(size: BoardSize, t: u32, input: &str) -> Result<GlobalId, AddressError> {
+    let gid = input.trim().parse::<usize>().map_err(|_| AddressError::NonIntegerGlobalId)?;
+    let (start_id, end_id) = id_range(size, t);
+    if gid < start_id.0 {
+        Err(AddressError::GlobalIdBelowRange { gid, min: start_id.0, t })
+    } else if gid > end_id.0 {
+        Err(AddressError::GlobalIdAboveRange { gid, max: end_id.0, t })
+    } else {
+        Ok(GlobalId(gid))
+    }
+}
+
+/// Parses a local ID and checks that it has exactly t digits from the alphabet.
+pub fn validate_local_id(t: u32, input: &str, alphabet: Alphabet) -> Result<LocalId, AddressError> {
+    let input = input.trim();
+    if input.len() != t as usize {
+        return Err(AddressError::LocalIdLength { expected: t as usize, t });
+    }
+    let in_alphabet = |c: char| c.to_digit(18).is_some_and(|d| (1..=alphabet.max_exponent()).contains(&d));
+    if !input.chars().all(in_alphabet) {
+        return Err(AddressError::LocalIdDigits(alphabet));
+    }
+    alphabet.parse(input).map(LocalId::new).map_err(AddressError::InvalidLocalId)
+}
+
+/// Canonical textual address of a board, written `t:global:local` (e.g. `2:119:AA`)
+/// for 4x4 boards and `RxC:t:global:local` (e.g. `3x3:2:5:11`) for other sizes.
+/// The local part may use the extended alphabet for tiles beyond 2048.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct BoardAddress {
+    pub size: BoardSize,
+    pub t: u32,
+    pub global_id: GlobalId,
+    pub local_id: LocalId,
+}
+
+impl BoardAddress {
+    /// Address of `board`, or why it has none: fewer than two tiles, a value that
+    /// is not a power of two, or tiles the extended local ID alphabet cannot
+    /// write (a repeated 2048 or larger, or anything above 131072).
+    pub fn try_from_board(board: &Board) -> Result<Self, AddressError> {
+        if let Some(&v) = board.rows().iter().flatten().find(|&&v| v == 1 || (v != 0 && !v.is_power_of_two())) {
+            return Err(AddressError::InvalidTile(v));
+        }
+        let (proto, tiles) = extract_proto_and_tiles(board);
+        let t = validate_t(proto.size(), &proto.t().to_string())?;
+        let global_id = proto.global_id().expect("t is in range");
+        let local_id = validate_local_id(t, &encode_base11(&tiles), Alphabet::Extended)?;
+        Ok(BoardAddress { size: proto.size(), t, global_id, local_id })
+    }
+
+    pub fn from_board(board: &Board) -> Option<Self> {
+        BoardAddress::try_from_board(board).ok()
+    }
+
+    pub fn protoboard(&self) -> Protoboard {
+        Protoboard::from_global_id(self.size, self.global_id)
+            .expect("address global ID is validated on construction")
+    }
+
+    pub fn to_board(&self) -> Board {
+        fill_board(&self.protoboard(), self.local_id.tiles())
+    }
+}
+
+impl FromStr for BoardAddress {
+    type Err = AddressError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<&str> = s.trim().split(':').collect();
+        let (size, t, gid, lid) = match parts[..] {
+            [t, gid, lid] => (BoardSize::CLASSIC, t, gid, lid),
+            [size, t, gid, lid] => (size.parse().map_err(AddressError::InvalidSize)?, t, gid, lid),
+            _ => return Err(AddressError::Malformed(s.to_string())),
+        };
+        let t = validate_t(size, t)?;
+        let global_id = validate_global_id(size, t, gid)?;
+        let local_id = validate_local_id(t, lid, Alphabet::Extended)?;
+        Ok(BoardAddress { size, t, global_id, local_id })
+    }
+}
+
+impl fmt::Display for BoardAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.size != BoardSize::CLASSIC {
+            write!(f, "{}:", self.size)?;
+        }
+        write!(f, "{}:{}:{}", self.t, self.global_id, self.local_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::library_index::LibraryIndex;
+
+    fn grid(rows: &[&[u32]]) -> Board {
+        Board::from_rows(rows.iter().map(|row| row.to_vec()).collect())
+    }
+
+    #[test]
+    fn display_parses_back() {
+        for size in BoardSize::all() {
+            for alphabet in [Alphabet::Standard, Alphabet::Extended] {
+                for i in 0..200u128 {
+                    let index = LibraryIndex(i * 7_919_999_999_999 + i);
+                    let Some(board) = index.to_board(size, alphabet) else {
+                        continue;
+                    };
+                    let address = BoardAddress::from_board(&board).expect("library boards have addresses");
+                    assert_eq!(address.to_string().parse::<BoardAddress>(), Ok(address.clone()));
+                    assert_eq!(address.to_board(), board);
+                }
+            }
+        }
+        let extended = grid(&[&[2048, 4096, 0, 0], &[0, 0, 0, 131072], &[0; 4], &[0, 0, 2, 2]]);
+        let address = BoardAddress::from_board(&extended).unwrap();
+        assert_eq!(address.to_string().parse::<BoardAddress>(), Ok(address));
+    }
+
+    #[test]
+    fn boards_outside_the_library_have_no_address() {
+        let repeated = grid(&[&[2048, 2048, 0, 0], &[0; 4], &[0; 4], &[0; 4]]);
+        assert!(matches!(BoardAddress::try_from_board(&repeated), Err(AddressError::InvalidLocalId(_))));
+        let too_large = grid(&[&[262144, 2, 0, 0], &[0; 4], &[0; 4], &[0; 4]]);
+        assert_eq!(BoardAddress::try_from_board(&too_large), Err(AddressError::LocalIdDigits(Alphabet::Extended)));
+        let single = grid(&[&[2, 0, 0, 0], &[0; 4], &[0; 4], &[0; 4]]);
+        assert_eq!(BoardAddress::try_from_board(&single), Err(AddressError::TOutOfRange { t: 1, max: 16 }));
+        let odd = grid(&[&[3, 2, 0, 0], &[0; 4], &[0; 4], &[0; 4]]);
+        assert_eq!(BoardAddress::try_from_board(&odd), Err(AddressError::InvalidTile(3)));
+        let one = grid(&[&[1, 2, 0, 0], &[0; 4], &[0; 4], &[0; 4]]);
+        assert_eq!(BoardAddress::try_from_board(&one), Err(AddressError::InvalidTile(1)));
+    }
+}
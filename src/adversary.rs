@@ -0,0 +1,74 @@
+use crate::bitboard::Bitboard;
+use crate::board::Board;
+use crate::expectimax::{Heuristic, LineHeuristic};
+use crate::spawn::{Spawn, SpawnPolicy};
+use crate::successors::{spawn_outcomes, successors};
+
+/// Number of player moves `Adversary` looks ahead after its spawn.
+pub const DEFAULT_DEPTH: u32 = 1;
+
+/// Spawns the tile that leaves the player worst off, by minimax over
+/// `successors`: the player maximises over moves and the spawn minimises over
+/// every 2 and 4 on every empty cell, the tiles of the classic rule. Leaves are
+/// scored by the heuristic on 4x4 boards and by their number of empty cells
+/// otherwise; a board with no move before the horizon scores 0.
+pub struct Adversary<H = LineHeuristic> {
+    heuristic: H,
+    depth: u32,
+}
+
+impl Default for Adversary {
+    fn default() -> Self {
+        Adversary::new(LineHeuristic, DEFAULT_DEPTH)
+    }
+}
+
+impl<H: Heuristic> Adversary<H> {
+    pub fn new(heuristic: H, depth: u32) -> Self {
+        Adversary { heuristic, depth }
+    }
+
+    pub fn depth(&self) -> u32 {
+        self.depth
+    }
+
+    /// The spawn on `afterstate` that minimises the player's best value `depth`
+    /// moves ahead, or None if it is full. Ties go to the first in row-major order.
+    pub fn worst_spawn(&self, afterstate: &Board) -> Option<Spawn> {
+        spawn_outcomes(afterstate)
+            .into_iter()
+            .map(|outcome| (outcome.spawn, self.max(&outcome.board, self.depth)))
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(spawn, _)| spawn)
+    }
+
+    // Player to move with `depth` moves left.
+    fn max(&self, board: &Board, depth: u32) -> f64 {
+        if depth == 0 {
+            return self.evaluate(board);
+        }
+        let mut best = 0.0;
+        for successor in successors(board) {
+            let worst = successor
+                .outcomes
+                .iter()
+                .map(|outcome| self.max(&outcome.board, depth - 1))
+                .fold(f64::INFINITY, f64::min);
+            best = f64::max(best, worst);
+        }
+        best
+    }
+
+    fn evaluate(&self, board: &Board) -> f64 {
+        match Bitboard::from_board(board) {
+            Some(packed) => self.heuristic.evaluate(packed),
+            None => board.empty_cells().len() as f64,
+        }
+    }
+}
+
+impl<H: Heuristic> SpawnPolicy for Adversary<H> {
+    fn spawn(&mut self, afterstate: &Board) -> Option<Spawn> {
+        self.worst_spawn(afterstate)
+    }
+}
@@ -0,0 +1,260 @@
+//! Headless command-line access to the library, for machines without a display.
+//!
+//! ```text
+//! library-cli [--json] decode [RxC] <t> <global> <local>
+//! library-cli [--json] decode <address>
+//! library-cli [--json] encode <grid>
+//! library-cli [--json] move <address> <dirs>
+//! library-cli [--json] successors <address>
+//! ```
+//!
+//! A grid lists rows separated by `/` and cells by `,`, with `0` or `.` for an
+//! empty cell, e.g. `2,0,0,0/0,0,0,0/0,0,0,0/0,0,0,2`. Directions are the letters
+//! `U`, `D`, `L` and `R`, e.g. `LLUR`.
+
+use std::env;
+use std::io::{self, Write};
+use std::process::ExitCode;
+
+use library_of_2048::{
+    AddressError, Alphabet, Board, BoardAddress, BoardSize, Direction, Protoboard, fill_board, move_board_scored,
+    successors, validate_global_id, validate_local_id, validate_t,
+};
+
+const USAGE: &str = "usage: library-cli [--json] <command>
+commands:
+  decode [RxC] <t> <global> <local>   board at an address given in parts
+  decode <address>                    board at an address such as 2:119:AA
+  encode <grid>                       address of a grid such as 2,0/0,2 (rows split by /)
+  move <address> <dirs>               play moves such as LLUR without spawns
+  successors <address>                every move and the spawns that can follow it";
+
+fn main() -> ExitCode {
+    let mut args: Vec<String> = env::args().skip(1).collect();
+    let json = args.iter().any(|arg| arg == "--json");
+    args.retain(|arg| arg != "--json");
+    match run(&args, json) {
+        Ok(output) => {
+            // A closed pipe, e.g. into `head`, is not an error.
+            let _ = writeln!(io::stdout(), "{}", output);
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(args: &[String], json: bool) -> Result<String, String> {
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    match args[..] {
+        ["decode", address] => Ok(show_board(&parse_address(address)?.to_board(), json)),
+        ["decode", t, gid, lid] => Ok(show_board(&decode(BoardSize::CLASSIC, t, gid, lid)?, json)),
+        ["decode", size, t, gid, lid] => Ok(show_board(&decode(size.parse()?, t, gid, lid)?, json)),
+        ["encode", grid] => Ok(show_board(&encode(grid)?, json)),
+        ["move", address, dirs] => play(&parse_address(address)?.to_board(), dirs, json),
+        ["successors", address] => Ok(show_successors(&parse_address(address)?.to_board(), json)),
+        _ => Err(USAGE.to_string()),
+    }
+}
+
+// The same checks the GUI makes on its t, global ID and local ID fields.
+fn decode(size: BoardSize, t: &str, gid: &str, lid: &str) -> Result<Board, String> {
+    let t = validate_t(size, t).map_err(|e| e.to_string())?;
+    let gid = validate_global_id(size, t, gid).map_err(|e| e.to_string())?;
+    let lid = validate_local_id(t, lid, Alphabet::Extended).map_err(|e| e.to_string())?;
+    let proto = Protoboard::from_global_id(size, gid).ok_or_else(|| format!("No protoboard with global ID {}", gid))?;
+    Ok(fill_board(&proto, lid.tiles()))
+}
+
+// A grid with an address, or the reason it has none, as the GUI editor reports it.
+fn encode(grid: &str) -> Result<Board, String> {
+    let board = parse_grid(grid)?;
+    BoardAddress::try_from_board(&board).map_err(|e| e.to_string())?;
+    Ok(board)
+}
+
+fn parse_address(s: &str) -> Result<BoardAddress, String> {
+    s.parse().map_err(|e: AddressError| e.to_string())
+}
+
+fn parse_grid(s: &str) -> Result<Board, String> {
+    let rows = s
+        .split('/')
+        .map(|row| row.split(',').map(parse_cell).collect::<Result<Vec<u32>, String>>())
+        .collect::<Result<Vec<_>, _>>()?;
+    let cols = rows[0].len();
+    if rows.iter().any(|row| row.len() != cols) {
+        return Err("Every row of the grid must have the same number of cells".to_string());
+    }
+    if BoardSize::new(rows.len(), cols).is_none() {
+        return Err(format!("Invalid board size {}x{}", rows.len(), cols));
+    }
+    Ok(Board::from_rows(rows))
+}
+
+fn parse_cell(s: &str) -> Result<u32, String> {
+    match s.trim() {
+        "." => Ok(0),
+        cell => match cell.parse::<u32>() {
+            Ok(v) if v == 0 || (v >= 2 && v.is_power_of_two()) => Ok(v),
+            _ => Err(format!("Invalid cell '{}'! Expected 0, . or a power of two", cell)),
+        },
+    }
+}
+
+fn parse_direction(c: char) -> Result<Direction, String> {
+    match c.to_ascii_uppercase() {
+        'U' => Ok(Direction::Up),
+        'D' => Ok(Direction::Down),
+        'L' => Ok(Direction::Left),
+        'R' => Ok(Direction::Right),
+        _ => Err(format!("Unknown direction '{}'! Expected U, D, L or R", c)),
+    }
+}
+
+fn play(start: &Board, dirs: &str, json: bool) -> Result<String, String> {
+    let mut board = start.clone();
+    let mut points = 0u64;
+    for c in dirs.chars() {
+        let (moved, gained) = move_board_scored(&board, parse_direction(c)?);
+        board = moved;
+        points += gained as u64;
+    }
+    Ok(if json {
+        format!("{{\"moves\":{},\"points\":{},\"board\":{}}}", quote(dirs), points, board_json(&board))
+    } else {
+        format!("moves {}  points {}\n{}", dirs, points, show_board(&board, false))
+    })
+}
+
+fn show_successors(board: &Board, json: bool) -> String {
+    let all = successors(board);
+    if json {
+        let moves: Vec<String> = all
+            .iter()
+            .map(|successor| {
+                let outcomes: Vec<String> = successor
+                    .outcomes
+                    .iter()
+                    .map(|outcome| {
+                        let spawn = outcome.spawn;
+                        format!(
+                            "{{\"row\":{},\"col\":{},\"value\":{},\"probability\":{},\"address\":{}}}",
+                            spawn.row, spawn.col, spawn.value(), outcome.probability, address_json(outcome.address.as_ref())
+                        )
+                    })
+                    .collect();
+                format!(
+                    "{{\"direction\":{},\"afterstate\":{},\"outcomes\":[{}]}}",
+                    quote(&format!("{:?}", successor.direction)),
+                    board_json(&successor.afterstate),
+                    outcomes.join(",")
+                )
+            })
+            .collect();
+        return format!("{{\"board\":{},\"successors\":[{}]}}", board_json(board), moves.join(","));
+    }
+    let mut out = show_board(board, false);
+    if all.is_empty() {
+        out.push_str("\nno moves left");
+    }
+    for successor in &all {
+        out.push_str(&format!("\n\n{:?}: {}", successor.direction, show_board(&successor.afterstate, false)));
+        for outcome in &successor.outcomes {
+            let spawn = outcome.spawn;
+            out.push_str(&format!(
+                "\n  {}@{},{}  p={:.4}  {}",
+                spawn.value(),
+                spawn.row,
+                spawn.col,
+                outcome.probability,
+                outcome.address.as_ref().map_or("(outside library)".to_string(), |a| a.to_string())
+            ));
+        }
+    }
+    out
+}
+
+// The board's address, or why it has none, above an ASCII grid.
+fn show_board(board: &Board, json: bool) -> String {
+    if json {
+        return board_json(board);
+    }
+    let width = board.rows().iter().flatten().map(|v| v.to_string().len()).max().unwrap_or(1);
+    let grid: Vec<String> = board
+        .rows()
+        .iter()
+        .map(|row| {
+            let cells: Vec<String> = row
+                .iter()
+                .map(|&v| if v == 0 { format!("{:>width$}", ".") } else { format!("{:>width$}", v) })
+                .collect();
+            cells.join(" ")
+        })
+        .collect();
+    let address = match BoardAddress::from_board(board) {
+        Some(address) => address.to_string(),
+        None => format!("(t={}, outside library)", board.rows().iter().flatten().filter(|&&v| v != 0).count()),
+    };
+    format!("{}\n{}", address, grid.join("\n"))
+}
+
+fn board_json(board: &Board) -> String {
+    let address = address_json(BoardAddress::from_board(board).as_ref());
+    let rows: Vec<String> = board
+        .rows()
+        .iter()
+        .map(|row| format!("[{}]", row.iter().map(u32::to_string).collect::<Vec<_>>().join(",")))
+        .collect();
+    format!("{{\"address\":{},\"size\":{},\"rows\":[{}]}}", address, quote(&board.size().to_string()), rows.join(","))
+}
+
+fn address_json(address: Option<&BoardAddress>) -> String {
+    address.map_or("null".to_string(), |a| quote(&a.to_string()))
+}
+
+fn quote(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grids_parse_row_by_row() {
+        let board = parse_grid("2,0,.,4/0,0,0,0/0,0,0,0/0,0,0,2048").unwrap();
+        assert_eq!(board, Board::from_rows(vec![vec![2, 0, 0, 4], vec![0; 4], vec![0; 4], vec![0, 0, 0, 2048]]));
+        assert_eq!(parse_grid(" 2 , 4 /0,0").unwrap(), Board::from_rows(vec![vec![2, 4], vec![0, 0]]));
+        assert!(parse_grid("2,0/0").is_err());
+        assert!(parse_grid("2").is_err());
+        assert!(parse_grid("2,0,0/0,0,0/0,0,0/0,0,0/0,0,0/0,0,0").is_err());
+    }
+
+    #[test]
+    fn cells_are_empty_or_powers_of_two() {
+        assert_eq!(parse_cell("."), Ok(0));
+        assert_eq!(parse_cell("0"), Ok(0));
+        assert_eq!(parse_cell(" 16 "), Ok(16));
+        for bad in ["1", "3", "-2", "x", ""] {
+            assert!(parse_cell(bad).is_err(), "{:?}", bad);
+        }
+    }
+
+    #[test]
+    fn directions_are_letters_in_either_case() {
+        assert_eq!(parse_direction('U'), Ok(Direction::Up));
+        assert_eq!(parse_direction('d'), Ok(Direction::Down));
+        assert_eq!(parse_direction('L'), Ok(Direction::Left));
+        assert_eq!(parse_direction('r'), Ok(Direction::Right));
+        assert!(parse_direction('X').is_err());
+    }
+
+    #[test]
+    fn encode_reports_boards_outside_the_library() {
+        assert!(encode("2,0/0,2").is_ok());
+        assert_eq!(encode("2048,2048/0,0").unwrap_err(), "Invalid local ID: more than one 'B'");
+    }
+}
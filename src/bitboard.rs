@@ -0,0 +1,199 @@
+use std::fmt;
+
+use crate::address::{BoardAddress, validate_local_id};
+use crate::board::{Board, Direction};
+use crate::local_id::{Alphabet, LocalId};
+use crate::move_table::{RowMove, move_row_left, move_row_right};
+use crate::protoboards::{GlobalId, Protoboard};
+use crate::size::BoardSize;
+
+/// Largest exponent a nibble cell can hold (2^15 = 32768).
+pub const MAX_NIBBLE_EXPONENT: u32 = 15;
+
+/// A 4x4 board packed into a u64, one nibble per cell holding the tile exponent
+/// (0 for an empty cell). Cell (row, col) lives in bits `4 * (row * 4 + col)..+4`,
+/// so cells are in the same order as protoboard bits and local ID digits.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Bitboard(pub u64);
+
+impl Bitboard {
+    pub fn get(self, row: usize, col: usize) -> u32 {
+        ((self.0 >> (4 * (row * 4 + col))) & 0xf) as u32
+    }
+
+    pub fn set(&mut self, row: usize, col: usize, exp: u32) {
+        debug_assert!(exp <= MAX_NIBBLE_EXPONENT);
+        let shift = 4 * (row * 4 + col);
+        self.0 = (self.0 & !(0xf << shift)) | ((exp as u64) << shift);
+    }
+
+    /// Row `row` as 16 bits, column 0 in the lowest nibble.
+    pub fn row(self, row: usize) -> u16 {
+        (self.0 >> (16 * row)) as u16
+    }
+
+    pub fn set_row(&mut self, row: usize, bits: u16) {
+        let shift = 16 * row;
+        self.0 = (self.0 & !(0xffff << shift)) | ((bits as u64) << shift);
+    }
+
+    /// Swaps rows and columns, so column moves can reuse row logic.
+    pub fn transpose(self) -> Self {
+        let x = self.0;
+        let a1 = x & 0xF0F0_0F0F_F0F0_0F0F;
+        let a2 = x & 0x0000_F0F0_0000_F0F0;
+        let a3 = x & 0x0F0F_0000_0F0F_0000;
+        let a = a1 | (a2 << 12) | (a3 >> 12);
+        let b1 = a & 0xFF00_FF00_00FF_00FF;
+        let b2 = a & 0x00FF_00FF_0000_0000;
+        let b3 = a & 0x0000_0000_FF00_FF00;
+        Bitboard(b1 | (b2 >> 24) | (b3 << 24))
+    }
+
+    /// Slides the board in `dir` using the precomputed row table, returning the new
+    /// board and the points gained. None if the move would merge two 2^15 tiles.
+    pub fn move_dir(self, dir: Direction) -> Option<(Bitboard, u32)> {
+        match dir {
+            Direction::Left => self.move_rows(move_row_left),
+            Direction::Right => self.move_rows(move_row_right),
+            Direction::Up => {
+                let (moved, score) = self.transpose().move_rows(move_row_left)?;
+                Some((moved.transpose(), score))
+            }
+            Direction::Down => {
+                let (moved, score) = self.transpose().move_rows(move_row_right)?;
+                Some((moved.transpose(), score))
+            }
+        }
+    }
+
+    fn move_rows(self, row_move: fn(u16) -> Option<RowMove>) -> Option<(Bitboard, u32)> {
+        let mut moved = self;
+        let mut score = 0;
+        for row in 0..4 {
+            let result = row_move(self.row(row))?;
+            moved.set_row(row, result.row);
+            score += result.score;
+        }
+        Some((moved, score))
+    }
+
+    pub fn count_empty(self) -> u32 {
+        (0..16).filter(|&i| (self.0 >> (4 * i)) & 0xf == 0).count() as u32
+    }
+
+    pub fn max_exponent(self) -> u32 {
+        (0..16).map(|i| ((self.0 >> (4 * i)) & 0xf) as u32).max().unwrap_or(0)
+    }
+
+    pub fn protoboard(self) -> Protoboard {
+        let mut mask = 0u32;
+        for i in 0..16 {
+            if (self.0 >> (4 * i)) & 0xf != 0 {
+                mask |= 1 << i;
+            }
+        }
+        Protoboard::from_mask(BoardSize::CLASSIC, mask)
+    }
+
+    pub fn local_id(self) -> LocalId {
+        let tiles = (0..16)
+            .map(|i| ((self.0 >> (4 * i)) & 0xf) as u32)
+            .filter(|&exp| exp != 0)
+            .collect();
+        LocalId::new(tiles)
+    }
+
+    /// Places the local ID's exponents on the protoboard's cells, in cell order.
+    /// None if the protoboard is not 4x4, the lengths disagree or an exponent
+    /// does not fit in a nibble.
+    pub fn from_ids(proto: Protoboard, lid: &LocalId) -> Option<Self> {
+        if proto.size() != BoardSize::CLASSIC || proto.t() as usize != lid.len() {
+            return None;
+        }
+        let mut packed = 0u64;
+        let mut tiles = lid.tiles().iter();
+        for i in 0..16 {
+            if proto.mask() >> i & 1 == 1 {
+                let exp = *tiles.next()?;
+                if exp > MAX_NIBBLE_EXPONENT {
+                    return None;
+                }
+                packed |= (exp as u64) << (4 * i);
+            }
+        }
+        Some(Bitboard(packed))
+    }
+
+    pub fn from_global_id(gid: GlobalId, lid: &LocalId) -> Option<Self> {
+        Bitboard::from_ids(Protoboard::from_global_id(BoardSize::CLASSIC, gid)?, lid)
+    }
+
+    pub fn from_address(address: &BoardAddress) -> Option<Self> {
+        Bitboard::from_ids(address.protoboard(), &address.local_id)
+    }
+
+    /// None if the board has fewer than two tiles or repeats a tile of 2048 or more.
+    pub fn address(self) -> Option<BoardAddress> {
+        let proto = self.protoboard();
+        let local_id = self.local_id();
+        validate_local_id(proto.t(), &local_id.to_string(), Alphabet::Extended).ok()?;
+        Some(BoardAddress { size: BoardSize::CLASSIC, t: proto.t(), global_id: proto.global_id()?, local_id })
+    }
+
+    /// Packs a 4x4 matrix board of tile values. None for other sizes, or if a
+    /// value is 1, not a power of two, or larger than 2^15.
+    pub fn from_board(board: &Board) -> Option<Self> {
+        if board.size() != BoardSize::CLASSIC {
+            return None;
+        }
+        let mut packed = Bitboard::default();
+        for (i, row) in board.rows().iter().enumerate() {
+            for (j, &v) in row.iter().enumerate() {
+                if v == 0 {
+                    continue;
+                }
+                // Exponent 0 marks an empty cell, so a 1 cannot be packed.
+                if v == 1 || !v.is_power_of_two() || v.ilog2() > MAX_NIBBLE_EXPONENT {
+                    return None;
+                }
+                packed.set(i, j, v.ilog2());
+            }
+        }
+        Some(packed)
+    }
+
+    pub fn to_board(self) -> Board {
+        let mut board = Board::empty(BoardSize::CLASSIC);
+        for i in 0..4 {
+            for j in 0..4 {
+                let exp = self.get(i, j);
+                if exp != 0 {
+                    board[i][j] = 1 << exp;
+                }
+            }
+        }
+        board
+    }
+}
+
+impl fmt::Display for Bitboard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:016x}", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::{Direction, move_board, move_board_reference};
+
+    #[test]
+    fn ones_are_not_packed_as_empty_cells() {
+        let board = Board::from_rows(vec![vec![1, 0, 0, 1], vec![0, 2, 0, 0], vec![0; 4], vec![0, 0, 0, 4]]);
+        assert_eq!(Bitboard::from_board(&board), None);
+        for dir in Direction::ALL {
+            assert_eq!(move_board(&board, dir), move_board_reference(&board, dir), "{:?}", dir);
+        }
+    }
+}
@@ -0,0 +1,242 @@
+use std::ops::{Index, IndexMut};
+
+use crate::bitboard::Bitboard;
+use crate::protoboards::Protoboard;
+use crate::size::BoardSize;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Direction {
+    pub const ALL: [Direction; 4] = [Direction::Up, Direction::Down, Direction::Left, Direction::Right];
+}
+
+/// A board of tile values (0 for an empty cell), indexed as `board[row][col]`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Board(Vec<Vec<u32>>);
+
+impl Board {
+    pub fn empty(size: BoardSize) -> Self {
+        Board(vec![vec![0; size.cols]; size.rows])
+    }
+
+    pub fn from_rows(rows: Vec<Vec<u32>>) -> Self {
+        Board(rows)
+    }
+
+    pub fn size(&self) -> BoardSize {
+        BoardSize { rows: self.0.len(), cols: self.0.first().map_or(0, Vec::len) }
+    }
+
+    pub fn rows(&self) -> &[Vec<u32>] {
+        &self.0
+    }
+
+    pub fn into_rows(self) -> Vec<Vec<u32>> {
+        self.0
+    }
+
+    pub fn empty_cells(&self) -> Vec<(usize, usize)> {
+        let mut cells = Vec::new();
+        for (i, row) in self.0.iter().enumerate() {
+            for (j, &v) in row.iter().enumerate() {
+                if v == 0 {
+                    cells.push((i, j));
+                }
+            }
+        }
+        cells
+    }
+}
+
+impl Index<usize> for Board {
+    type Output = Vec<u32>;
+
+    fn index(&self, row: usize) -> &Vec<u32> {
+        &self.0[row]
+    }
+}
+
+impl IndexMut<usize> for Board {
+    fn index_mut(&mut self, row: usize) -> &mut Vec<u32> {
+        &mut self.0[row]
+    }
+}
+
+pub fn fill_board(proto: &Protoboard, tiles: &[u32]) -> Board {
+    let size = proto.size();
+    let mut filled = Board::empty(size);
+    let mut iter = tiles.iter();
+    for i in 0..size.rows {
+        for j in 0..size.cols {
+            if proto.is_filled(i, j) {
+                let exp = *iter.next().unwrap();
+                filled[i][j] = 2u32.pow(exp);
+            }
+        }
+    }
+    filled
+}
+
+// Search for new board based on move
+pub fn slide_and_merge_line(line: Vec<u32>) -> Vec<u32> {
+    slide_and_merge_line_merges(line).0
+}
+
+// Also returns each merge as (index in the result, new tile value).
+fn slide_and_merge_line_merges(mut line: Vec<u32>) -> (Vec<u32>, Vec<(usize, u32)>) {
+    let len = line.len();
+    // Remove zeros
+    line.retain(|&x| x != 0);
+    let mut result = Vec::with_capacity(len);
+    let mut merges = Vec::new();
+    let mut skip = false;
+    for i in 0..line.len() {
+        if skip {
+            skip = false;
+            continue;
+        }
+        if i + 1 < line.len() && line[i] == line[i + 1] {
+            merges.push((result.len(), line[i] * 2));
+            result.push(line[i] * 2);
+            skip = true;
+        } else {
+            result.push(line[i]);
+        }
+    }
+    // Pad with zeros
+    while result.len() < len {
+        result.push(0);
+    }
+    (result, merges)
+}
+
+/// Two equal tiles combined by a move into a tile of `value` at (`row`, `col`).
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Merge {
+    pub row: usize,
+    pub col: usize,
+    pub value: u32,
+}
+
+/// The result of a move: the new board, the points gained (the sum of the
+/// merged tiles' values) and the merges performed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MoveReport {
+    pub board: Board,
+    pub score: u32,
+    pub merges: Vec<Merge>,
+}
+
+/// Moves a board through the packed row table when it is 4x4, falling back to
+/// the per-line reference implementation for other sizes and for tiles or
+/// merges beyond 2^15.
+pub fn move_board(board: &Board, dir: Direction) -> Board {
+    move_board_scored(board, dir).0
+}
+
+/// Like `move_board`, also returning the points gained by the move's merges.
+pub fn move_board_scored(board: &Board, dir: Direction) -> (Board, u32) {
+    match Bitboard::from_board(board).and_then(|packed| packed.move_dir(dir)) {
+        Some((moved, score)) => (moved.to_board(), score),
+        None => {
+            let report = move_board_report(board, dir);
+            (report.board, report.score)
+        }
+    }
+}
+
+pub fn move_board_reference(board: &Board, dir: Direction) -> Board {
+    move_board_report(board, dir).board
+}
+
+/// Moves a board line by line, recording every merge.
+pub fn move_board_report(board: &Board, dir: Direction) -> MoveReport {
+    let size = board.size();
+    let mut new_board = Board::empty(size);
+    let mut merges = Vec::new();
+    match dir {
+        Direction::Left => {
+            for i in 0..size.rows {
+                let row = board[i].clone();
+                let (merged, line_merges) = slide_and_merge_line_merges(row);
+                new_board[i] = merged;
+                merges.extend(line_merges.into_iter().map(|(j, value)| Merge { row: i, col: j, value }));
+            }
+        }
+        Direction::Right => {
+            for i in 0..size.rows {
+                let mut row = board[i].clone();
+                row.reverse();
+                let (mut merged, line_merges) = slide_and_merge_line_merges(row);
+                merged.reverse();
+                new_board[i] = merged;
+                merges.extend(line_merges.into_iter().map(|(j, value)| Merge { row: i, col: size.cols - 1 - j, value }));
+            }
+        }
+        Direction::Up => {
+            for j in 0..size.cols {
+                let col: Vec<u32> = (0..size.rows).map(|i| board[i][j]).collect();
+                let (merged, line_merges) = slide_and_merge_line_merges(col);
+                for (i, &v) in merged.iter().enumerate() {
+                    new_board[i][j] = v;
+                }
+                merges.extend(line_merges.into_iter().map(|(i, value)| Merge { row: i, col: j, value }));
+            }
+        }
+        Direction::Down => {
+            for j in 0..size.cols {
+                let mut col: Vec<u32> = (0..size.rows).map(|i| board[i][j]).collect();
+                col.reverse();
+                let (mut merged, line_merges) = slide_and_merge_line_merges(col);
+                merged.reverse();
+                for (i, &v) in merged.iter().enumerate() {
+                    new_board[i][j] = v;
+                }
+                merges.extend(line_merges.into_iter().map(|(i, value)| Merge { row: size.rows - 1 - i, col: j, value }));
+            }
+        }
+    }
+    let score = merges.iter().map(|merge| merge.value).sum();
+    MoveReport { board: new_board, score, merges }
+}
+
+pub fn extract_proto_and_tiles(board: &Board) -> (Protoboard, Vec<u32>) {
+    let size = board.size();
+    let mut proto = Protoboard::empty(size);
+    let mut tiles = Vec::new();
+    for i in 0..size.rows {
+        for j in 0..size.cols {
+            if board[i][j] != 0 {
+                proto.set_filled(i, j);
+                tiles.push(board[i][j].ilog2());
+            }
+        }
+    }
+    (proto, tiles)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mirrored_moves_report_merges_where_they_land() {
+        let row = Board::from_rows(vec![vec![2, 2, 4, 4], vec![0; 4], vec![0; 4], vec![0; 4]]);
+        let right = move_board_report(&row, Direction::Right);
+        assert_eq!(right.board[0], vec![0, 0, 4, 8]);
+        assert_eq!(right.merges, vec![Merge { row: 0, col: 3, value: 8 }, Merge { row: 0, col: 2, value: 4 }]);
+        assert_eq!(right.score, 12);
+
+        let column = Board::from_rows(vec![vec![2, 0, 0], vec![2, 0, 0], vec![4, 0, 0], vec![4, 0, 0]]);
+        let down = move_board_report(&column, Direction::Down);
+        assert_eq!(down.board, Board::from_rows(vec![vec![0, 0, 0], vec![0, 0, 0], vec![4, 0, 0], vec![8, 0, 0]]));
+        assert_eq!(down.merges, vec![Merge { row: 3, col: 0, value: 8 }, Merge { row: 2, col: 0, value: 4 }]);
+        assert_eq!(down.score, 12);
+    }
+}
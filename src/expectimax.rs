@@ -0,0 +1,172 @@
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+use crate::bitboard::Bitboard;
+use crate::board::Direction;
+use crate::spawn::{Spawn, TWO_PROBABILITY};
+
+/// Number of move-then-spawn plies searched by `expectimax`.
+pub const DEFAULT_DEPTH: u32 = 3;
+
+/// Chance nodes reached with a lower cumulative probability than this are scored
+/// by the heuristic instead of being expanded.
+pub const MIN_PROBABILITY: f64 = 1e-4;
+
+/// Static evaluation of a board at the search horizon; larger is better.
+/// Boards with no legal move score 0, so evaluations should be non-negative.
+pub trait Heuristic {
+    fn evaluate(&self, board: Bitboard) -> f64;
+}
+
+impl<F: Fn(Bitboard) -> f64> Heuristic for F {
+    fn evaluate(&self, board: Bitboard) -> f64 {
+        self(board)
+    }
+}
+
+/// Scores a board by its number of empty cells.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct EmptyCells;
+
+impl Heuristic for EmptyCells {
+    fn evaluate(&self, board: Bitboard) -> f64 {
+        board.count_empty() as f64
+    }
+}
+
+/// Sums a per-line score over every row and column, rewarding empty cells,
+/// adjacent equal tiles and monotonic lines, and penalising large scattered tiles.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct LineHeuristic;
+
+impl Heuristic for LineHeuristic {
+    fn evaluate(&self, board: Bitboard) -> f64 {
+        let transposed = board.transpose();
+        (0..4)
+            .map(|i| LINE_SCORES[board.row(i) as usize] + LINE_SCORES[transposed.row(i) as usize])
+            .sum()
+    }
+}
+
+// Indexed by packed row, like the move table.
+static LINE_SCORES: LazyLock<Box<[f64]>> = LazyLock::new(|| (0..=u16::MAX).map(score_line).collect());
+
+fn score_line(row: u16) -> f64 {
+    let line: Vec<f64> = (0..4).map(|i| ((row >> (4 * i)) & 0xf) as f64).collect();
+    let empty = line.iter().filter(|&&exp| exp == 0.0).count() as f64;
+    let sum: f64 = line.iter().map(|exp| exp.powf(3.5)).sum();
+
+    let mut merges = 0.0;
+    let mut run = 0.0;
+    let mut prev = 0.0;
+    for &exp in line.iter().filter(|&&exp| exp != 0.0) {
+        if exp == prev {
+            run += 1.0;
+        } else if run > 0.0 {
+            merges += 1.0 + run;
+            run = 0.0;
+        }
+        prev = exp;
+    }
+    if run > 0.0 {
+        merges += 1.0 + run;
+    }
+
+    let (mut decreasing, mut increasing) = (0.0, 0.0);
+    for pair in line.windows(2) {
+        let (a, b) = (pair[0].powi(4), pair[1].powi(4));
+        if a > b {
+            decreasing += a - b;
+        } else {
+            increasing += b - a;
+        }
+    }
+
+    200_000.0 + 270.0 * empty + 700.0 * merges - 47.0 * f64::min(decreasing, increasing) - 11.0 * sum
+}
+
+/// Expected value of playing `direction` and then searching to the horizon.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct MoveValue {
+    pub direction: Direction,
+    pub value: f64,
+}
+
+/// Depth-limited expectimax search: the player maximises over moves, spawns are
+/// averaged with the classic 2/4 probabilities. Afterstates are cached by remaining
+/// depth for the duration of one `evaluate` call.
+pub struct Expectimax<H = LineHeuristic> {
+    heuristic: H,
+    depth: u32,
+    cache: HashMap<(Bitboard, u32), f64>,
+}
+
+impl<H: Heuristic> Expectimax<H> {
+    pub fn new(heuristic: H, depth: u32) -> Self {
+        Expectimax { heuristic, depth: depth.max(1), cache: HashMap::new() }
+    }
+
+    /// Expected value of every legal move from `board`, in `Direction::ALL` order.
+    pub fn evaluate(&mut self, board: Bitboard) -> Vec<MoveValue> {
+        self.cache.clear();
+        Direction::ALL
+            .into_iter()
+            .filter_map(|direction| {
+                let (afterstate, _) = board.move_dir(direction)?;
+                (afterstate != board).then(|| MoveValue { direction, value: self.chance(afterstate, self.depth, 1.0) })
+            })
+            .collect()
+    }
+
+    /// The highest-valued legal move, or None if the game is over.
+    pub fn best_move(&mut self, board: Bitboard) -> Option<MoveValue> {
+        self.evaluate(board).into_iter().max_by(|a, b| a.value.total_cmp(&b.value))
+    }
+
+    // Player to move with `depth` plies left.
+    fn max(&mut self, board: Bitboard, depth: u32, probability: f64) -> f64 {
+        if depth == 0 {
+            return self.heuristic.evaluate(board);
+        }
+        let mut best = 0.0;
+        for direction in Direction::ALL {
+            if let Some((afterstate, _)) = board.move_dir(direction)
+                && afterstate != board
+            {
+                best = f64::max(best, self.chance(afterstate, depth, probability));
+            }
+        }
+        best
+    }
+
+    // Spawn to come on `afterstate`; `probability` is the chance of reaching it.
+    fn chance(&mut self, afterstate: Bitboard, depth: u32, probability: f64) -> f64 {
+        if probability < MIN_PROBABILITY {
+            return self.heuristic.evaluate(afterstate);
+        }
+        if let Some(&value) = self.cache.get(&(afterstate, depth)) {
+            return value;
+        }
+        let empty = afterstate.count_empty() as f64;
+        let mut total = 0.0;
+        for row in 0..4 {
+            for col in 0..4 {
+                if afterstate.get(row, col) != 0 {
+                    continue;
+                }
+                for (exponent, weight) in [(1, TWO_PROBABILITY), (2, 1.0 - TWO_PROBABILITY)] {
+                    let spawned = Spawn { row, col, exponent }.apply(afterstate);
+                    total += weight * self.max(spawned, depth - 1, probability * weight / empty);
+                }
+            }
+        }
+        let value = total / empty;
+        self.cache.insert((afterstate, depth), value);
+        value
+    }
+}
+
+/// Expected values of every legal move from `board` under `LineHeuristic`.
+pub fn expectimax(board: Bitboard, depth: u32) -> Vec<MoveValue> {
+    Expectimax::new(LineHeuristic, depth).evaluate(board)
+}
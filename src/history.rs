@@ -0,0 +1,75 @@
+/// A linear undo/redo history with a cursor on the current entry. Pushing after
+/// an undo drops the undone entries; jumping only moves the cursor.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct History<T> {
+    entries: Vec<T>,
+    current: usize,
+}
+
+impl<T> Default for History<T> {
+    fn default() -> Self {
+        History { entries: Vec::new(), current: 0 }
+    }
+}
+
+impl<T> History<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Makes `entry` the current entry, discarding anything that could be redone.
+    pub fn push(&mut self, entry: T) {
+        if !self.entries.is_empty() {
+            self.entries.truncate(self.current + 1);
+        }
+        self.entries.push(entry);
+        self.current = self.entries.len() - 1;
+    }
+
+    pub fn current(&self) -> Option<&T> {
+        self.entries.get(self.current)
+    }
+
+    pub fn current_index(&self) -> Option<usize> {
+        (!self.entries.is_empty()).then_some(self.current)
+    }
+
+    pub fn entries(&self) -> &[T] {
+        &self.entries
+    }
+
+    pub fn can_undo(&self) -> bool {
+        self.current > 0
+    }
+
+    pub fn can_redo(&self) -> bool {
+        self.current + 1 < self.entries.len()
+    }
+
+    /// Steps back one entry, returning the new current entry.
+    pub fn undo(&mut self) -> Option<&T> {
+        if !self.can_undo() {
+            return None;
+        }
+        self.current -= 1;
+        self.current()
+    }
+
+    /// Steps forward one entry, returning the new current entry.
+    pub fn redo(&mut self) -> Option<&T> {
+        if !self.can_redo() {
+            return None;
+        }
+        self.current += 1;
+        self.current()
+    }
+
+    /// Moves the cursor to entry `index`, keeping every entry.
+    pub fn jump(&mut self, index: usize) -> Option<&T> {
+        if index >= self.entries.len() {
+            return None;
+        }
+        self.current = index;
+        self.current()
+    }
+}
@@ -0,0 +1,50 @@
+//! Headless core of the Library of 2048: board types, ID encoding and the
+//! move engine. The eframe GUI in `main.rs` is one consumer of this crate.
+
+pub mod address;
+pub mod adversary;
+pub mod bitboard;
+pub mod board;
+pub mod expectimax;
+pub mod history;
+pub mod library_index;
+pub mod local_id;
+pub mod move_table;
+pub mod ntuple;
+pub mod predecessors;
+pub mod protoboards;
+pub mod reachability;
+pub mod recording;
+pub mod retrograde;
+pub mod score;
+pub mod selfplay;
+pub mod size;
+pub mod spawn;
+pub mod status;
+pub mod successors;
+pub mod symmetry;
+
+pub use address::{AddressError, BoardAddress, validate_global_id, validate_local_id, validate_t};
+pub use adversary::Adversary;
+pub use bitboard::Bitboard;
+pub use board::{
+    Board, Direction, Merge, MoveReport, extract_proto_and_tiles, fill_board, move_board, move_board_reference,
+    move_board_report, move_board_scored, slide_and_merge_line,
+};
+pub use expectimax::{EmptyCells, Expectimax, Heuristic, LineHeuristic, MoveValue, expectimax};
+pub use history::History;
+pub use library_index::{LibraryIndex, library_size};
+pub use local_id::{Alphabet, LocalId, encode_base11, parse_base11, parse_extended};
+pub use ntuple::{Afterstate, NTupleNetwork};
+pub use predecessors::{Predecessor, predecessors, unmove};
+pub use protoboards::{GlobalId, MIN_T, Protoboard, count_filled, id_range, max_t};
+pub use reachability::{Proof, Reachability, Step, Unreachable, classify, classify_with_budget};
+pub use recording::{Recording, ReplayStep, Turn};
+pub use retrograde::RetrogradeTable;
+pub use score::min_score;
+pub use selfplay::{GameResult, Policy, RandomPolicy, play_game, play_games};
+pub use size::BoardSize;
+pub use spawn::{Spawn, SpawnModel, SpawnPolicy, TWO_PROBABILITY};
+pub use status::{GameStatus, is_legal, legal_moves};
+pub use successors::{Outcome, Successors, spawn_outcomes, successors};
+pub use symmetry::{Canonical, Symmetry, canonicalize};
@@ -0,0 +1,131 @@
+use std::fmt;
+
+use crate::address::{AddressError, BoardAddress, validate_local_id};
+use crate::board::{Board, fill_board};
+use crate::local_id::{Alphabet, LocalId, local_id_count};
+use crate::protoboards::{GlobalId, MIN_T, Protoboard, binomial, id_range, max_t, protoboard_count};
+use crate::size::BoardSize;
+
+/// Total number of boards of one size in the library across every t, global ID and local ID.
+pub fn library_size(size: BoardSize, alphabet: Alphabet) -> u128 {
+    boards_before_t(size, max_t(size) + 1, alphabet)
+}
+
+// Number of boards with fewer than t tiles.
+fn boards_before_t(size: BoardSize, t: u32, alphabet: Alphabet) -> u128 {
+    (MIN_T..t).map(|k| binomial(max_t(size), k) as u128 * local_id_count(k, alphabet)).sum()
+}
+
+/// Absolute position of a board in the library, starting at 0. Boards are ordered
+/// by t, then by global ID, then by local ID in ascending base-11 order, so the
+/// index of every board with a given t or global ID forms one contiguous range.
+/// Indexes are only comparable within one board size and local ID alphabet.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct LibraryIndex(pub u128);
+
+impl LibraryIndex {
+    pub fn from_ids(size: BoardSize, gid: GlobalId, lid: &LocalId, alphabet: Alphabet) -> Result<Self, AddressError> {
+        let t = match gid.t(size) {
+            Some(t) => t,
+            None if gid.0 < 1 => return Err(AddressError::GlobalIdBelowRange { gid: gid.0, min: 1, t: MIN_T }),
+            None => {
+                let (max, t) = (protoboard_count(size), max_t(size));
+                return Err(AddressError::GlobalIdAboveRange { gid: gid.0, max, t });
+            }
+        };
+        validate_local_id(t, &lid.to_string(), alphabet)?;
+        let (start, _) = id_range(size, t);
+        let index = boards_before_t(size, t, alphabet)
+            + (gid.0 - start.0) as u128 * local_id_count(t, alphabet)
+            + lid.rank(alphabet);
+        Ok(LibraryIndex(index))
+    }
+
+    pub fn to_ids(self, size: BoardSize, alphabet: Alphabet) -> Option<(GlobalId, LocalId)> {
+        let mut t = MIN_T;
+        while t < max_t(size) && boards_before_t(size, t + 1, alphabet) <= self.0 {
+            t += 1;
+        }
+        let offset = self.0.checked_sub(boards_before_t(size, t, alphabet))?;
+        let per_proto = local_id_count(t, alphabet);
+        let (start, end) = id_range(size, t);
+        let gid = GlobalId(start.0 + (offset / per_proto) as usize);
+        if gid > end {
+            return None;
+        }
+        let lid = LocalId::unrank(t, offset % per_proto, alphabet)?;
+        Some((gid, lid))
+    }
+
+    pub fn from_board(board: &Board, alphabet: Alphabet) -> Option<Self> {
+        let address = BoardAddress::from_board(board)?;
+        LibraryIndex::from_ids(address.size, address.global_id, &address.local_id, alphabet).ok()
+    }
+
+    pub fn to_board(self, size: BoardSize, alphabet: Alphabet) -> Option<Board> {
+        let (gid, lid) = self.to_ids(size, alphabet)?;
+        let proto = Protoboard::from_global_id(size, gid)?;
+        Some(fill_board(&proto, lid.tiles()))
+    }
+}
+
+impl fmt::Display for LibraryIndex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALPHABETS: [Alphabet; 2] = [Alphabet::Standard, Alphabet::Extended];
+
+    fn assert_round_trip(size: BoardSize, index: LibraryIndex, alphabet: Alphabet) {
+        let (gid, lid) = index.to_ids(size, alphabet).expect("index below the library size");
+        assert_eq!(LibraryIndex::from_ids(size, gid, &lid, alphabet), Ok(index), "{} on {}", index, size);
+        let board = index.to_board(size, alphabet).expect("index below the library size");
+        assert_eq!(board.size(), size);
+        assert_eq!(LibraryIndex::from_board(&board, alphabet), Some(index));
+    }
+
+    #[test]
+    fn classic_library_size() {
+        assert_eq!(library_size(BoardSize::CLASSIC, Alphabet::Standard), 112_785_700_574_222_400);
+    }
+
+    #[test]
+    fn every_2x2_board_round_trips() {
+        let size = BoardSize::new(2, 2).unwrap();
+        for index in 0..library_size(size, Alphabet::Standard) {
+            assert_round_trip(size, LibraryIndex(index), Alphabet::Standard);
+        }
+    }
+
+    #[test]
+    fn sampled_indexes_round_trip_on_every_size() {
+        for size in BoardSize::all() {
+            for alphabet in ALPHABETS {
+                let total = library_size(size, alphabet);
+                for index in (0..100).map(|i| total / 100 * i).chain([1, total - 1]) {
+                    assert_round_trip(size, LibraryIndex(index), alphabet);
+                }
+                assert_eq!(LibraryIndex(total).to_ids(size, alphabet), None);
+            }
+        }
+    }
+
+    #[test]
+    fn range_ends_round_trip() {
+        for size in BoardSize::all() {
+            for t in MIN_T..=max_t(size) {
+                let (start, end) = id_range(size, t);
+                for gid in [start, end] {
+                    let lid = LocalId::new(vec![1; t as usize]);
+                    let index = LibraryIndex::from_ids(size, gid, &lid, Alphabet::Standard).unwrap();
+                    assert_eq!(index.to_ids(size, Alphabet::Standard), Some((gid, lid)));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,255 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::protoboards::binomial;
+
+/// Exponent written as 'B' (2048); a local ID may contain at most one.
+pub const MAX_EXPONENT: u32 = 11;
+
+/// Largest exponent of the extended alphabet, written 'H' (131072): the largest
+/// tile a 4x4 board can hold.
+pub const MAX_EXTENDED_EXPONENT: u32 = 17;
+
+/// Digit sets a local ID can be written in. Both share digits 1-9 and A, and
+/// allow each digit from B upwards at most once per board; they differ in how
+/// many such digits exist, and so in how local IDs are ranked.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Alphabet {
+    /// Digits [1-9, A, B]: tiles up to 2048.
+    #[default]
+    Standard,
+    /// Digits [1-9, A-H]: tiles up to 131072.
+    Extended,
+}
+
+impl Alphabet {
+    pub fn max_exponent(self) -> u32 {
+        match self {
+            Alphabet::Standard => MAX_EXPONENT,
+            Alphabet::Extended => MAX_EXTENDED_EXPONENT,
+        }
+    }
+
+    /// Human-readable digit list, for labels and error messages.
+    pub fn digits(self) -> &'static str {
+        match self {
+            Alphabet::Standard => "1-9, A, or B (base-11)",
+            Alphabet::Extended => "1-9 or A-H (extended)",
+        }
+    }
+
+    pub fn parse(self, s: &str) -> Result<Vec<u32>, String> {
+        match self {
+            Alphabet::Standard => parse_base11(s),
+            Alphabet::Extended => parse_extended(s),
+        }
+    }
+
+    // Digits from B upwards, each usable once per local ID.
+    fn unique_digits(self) -> u32 {
+        self.max_exponent() - MAX_EXPONENT + 1
+    }
+}
+
+fn is_unique_digit(exp: u32) -> bool {
+    exp >= MAX_EXPONENT
+}
+
+/// Number of valid local IDs of length t in the given alphabet.
+pub fn local_id_count(t: u32, alphabet: Alphabet) -> u128 {
+    count_suffixes(t, alphabet.unique_digits())
+}
+
+// Strings of `len` digits where each of `unique_left` digits may appear at most
+// once and the 10 digits 1-A are unrestricted: choose k positions for distinct
+// unique digits, fill the rest freely.
+fn count_suffixes(len: u32, unique_left: u32) -> u128 {
+    (0..=len.min(unique_left))
+        .map(|k| {
+            let arrangements: u128 = (0..k).map(|i| (unique_left - i) as u128).product();
+            binomial(len, k) as u128 * arrangements * 10u128.pow(len - k)
+        })
+        .sum()
+}
+
+/// Tile exponents of a board in left-to-right, top-to-bottom order,
+/// written as a base-11 string with digits [1-9, A, B] (or [1-9, A-H]
+/// in the extended alphabet).
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct LocalId(Vec<u32>);
+
+impl LocalId {
+    pub fn new(tiles: Vec<u32>) -> Self {
+        LocalId(tiles)
+    }
+
+    pub fn tiles(&self) -> &[u32] {
+        &self.0
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Position of this ID among all valid IDs of the same length, in ascending digit order.
+    pub fn rank(&self, alphabet: Alphabet) -> u128 {
+        let mut rank = 0;
+        let mut used = Vec::new();
+        for (i, &d) in self.0.iter().enumerate() {
+            let remaining = (self.0.len() - i - 1) as u32;
+            let unique_left = alphabet.unique_digits() - used.len() as u32;
+            for smaller in 1..d {
+                if is_unique_digit(smaller) {
+                    if !used.contains(&smaller) {
+                        rank += count_suffixes(remaining, unique_left - 1);
+                    }
+                } else {
+                    rank += count_suffixes(remaining, unique_left);
+                }
+            }
+            if is_unique_digit(d) {
+                used.push(d);
+            }
+        }
+        rank
+    }
+
+    /// Inverse of `rank`: the valid ID of length t at the given position, if any.
+    pub fn unrank(t: u32, mut rank: u128, alphabet: Alphabet) -> Option<Self> {
+        if rank >= local_id_count(t, alphabet) {
+            return None;
+        }
+        let mut tiles = Vec::with_capacity(t as usize);
+        let mut used = Vec::new();
+        for i in 0..t {
+            let remaining = t - i - 1;
+            let unique_left = alphabet.unique_digits() - used.len() as u32;
+            let mut placed = false;
+            for d in 1..=alphabet.max_exponent() {
+                let count = if !is_unique_digit(d) {
+                    count_suffixes(remaining, unique_left)
+                } else if used.contains(&d) {
+                    continue;
+                } else {
+                    count_suffixes(remaining, unique_left - 1)
+                };
+                if rank < count {
+                    tiles.push(d);
+                    if is_unique_digit(d) {
+                        used.push(d);
+                    }
+                    placed = true;
+                    break;
+                }
+                rank -= count;
+            }
+            if !placed {
+                return None;
+            }
+        }
+        Some(LocalId(tiles))
+    }
+}
+
+impl FromStr for LocalId {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_extended(s).map(LocalId)
+    }
+}
+
+impl fmt::Display for LocalId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&encode_base11(&self.0))
+    }
+}
+
+pub fn parse_base11(s: &str) -> Result<Vec<u32>, String> {
+    if s.chars().filter(|&c| c == 'B' || c == 'b').count() > 1 {
+        return Err("Invalid base-11 ID: more than one 'B'".to_string());
+    }
+    s.chars().map(|c| match c {
+        '1'..='9' => Ok(c.to_digit(11).unwrap()),
+        'A' | 'a' => Ok(10),
+        'B' | 'b' => Ok(11),
+        _ => Err(format!("Invalid base-11 digit: {}", c)),
+    }).collect()
+}
+
+/// Like `parse_base11`, but also accepts C-H (4096 to 131072), each at most once.
+pub fn parse_extended(s: &str) -> Result<Vec<u32>, String> {
+    let tiles = s.chars().map(|c| match c.to_digit(18) {
+        Some(d) if d >= 1 => Ok(d),
+        _ => Err(format!("Invalid extended digit: {}", c)),
+    }).collect::<Result<Vec<u32>, String>>()?;
+    for exp in MAX_EXPONENT..=MAX_EXTENDED_EXPONENT {
+        if tiles.iter().filter(|&&d| d == exp).count() > 1 {
+            let digit = std::char::from_digit(exp, 18).unwrap().to_ascii_uppercase();
+            return Err(format!("Invalid local ID: more than one '{}'", digit));
+        }
+    }
+    Ok(tiles)
+}
+
+pub fn encode_base11(tiles: &[u32]) -> String {
+    tiles.iter().map(|&v| {
+        match v {
+            1..=9 => std::char::from_digit(v, 10).unwrap(),
+            10..=MAX_EXTENDED_EXPONENT => std::char::from_digit(v, 18).unwrap().to_ascii_uppercase(),
+            _ => '?'
+        }
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Every digit string of length t in ascending order, valid or not.
+    fn all_strings(t: u32, alphabet: Alphabet) -> Vec<Vec<u32>> {
+        let base = alphabet.max_exponent();
+        (0..base.pow(t))
+            .map(|n| (0..t).rev().map(|i| n / base.pow(i) % base + 1).collect())
+            .collect()
+    }
+
+    fn is_valid(tiles: &[u32]) -> bool {
+        tiles.iter().enumerate().all(|(i, &d)| !is_unique_digit(d) || !tiles[..i].contains(&d))
+    }
+
+    #[test]
+    fn rank_counts_valid_ids_in_ascending_order() {
+        for alphabet in [Alphabet::Standard, Alphabet::Extended] {
+            for t in 1..=3 {
+                let valid: Vec<Vec<u32>> = all_strings(t, alphabet).into_iter().filter(|s| is_valid(s)).collect();
+                assert_eq!(valid.len() as u128, local_id_count(t, alphabet));
+                for (rank, tiles) in valid.into_iter().enumerate() {
+                    let lid = LocalId::new(tiles);
+                    assert_eq!(lid.rank(alphabet), rank as u128, "{} in {:?}", lid, alphabet);
+                    assert_eq!(LocalId::unrank(t, rank as u128, alphabet), Some(lid));
+                }
+                assert_eq!(LocalId::unrank(t, local_id_count(t, alphabet), alphabet), None);
+            }
+        }
+    }
+
+    #[test]
+    fn unrank_inverts_rank_on_long_ids() {
+        for alphabet in [Alphabet::Standard, Alphabet::Extended] {
+            for t in [8, 16] {
+                let count = local_id_count(t, alphabet);
+                for rank in (0..1000).map(|i| count / 1000 * i).chain([count - 1]) {
+                    let lid = LocalId::unrank(t, rank, alphabet).expect("rank below the count");
+                    assert_eq!(lid.len(), t as usize);
+                    assert!(is_valid(lid.tiles()), "{} in {:?}", lid, alphabet);
+                    assert_eq!(lid.rank(alphabet), rank);
+                }
+            }
+        }
+    }
+}
@@ -1,84 +1,28 @@
+use std::fs;
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+use std::time::Duration;
+
 use eframe::{egui, App, Frame, NativeOptions};
 use egui::text::{CCursor, CCursorRange};
-use std::collections::HashMap;
-use std::fs::File;
-use std::io::{BufRead, BufReader};
-use std::path::Path;
-use rand::prelude::*;
-use rand::Rng;
-mod protoboards;
-
-// Parse protoboards.txt into map: t -> Vec<(global_id, board matrix)>
-fn parse_protoboards(path: &str) -> HashMap<u32, Vec<(usize, Vec<Vec<char>>)>> {
-    if !Path::new(path).exists() {
-        protoboards::generate_protoboards();
-    }
-    
-    let file = File::open(Path::new(path)).expect("Cannot open protoboards.txt");
-    let reader = BufReader::new(file);
-    let mut map: HashMap<u32, Vec<(usize, Vec<Vec<char>>)>> = HashMap::new();
-    let mut current_t = 0;
-    let mut current_id = 0;
-    let mut current_board: Vec<Vec<char>> = Vec::new();
-    for line in reader.lines() {
-        let line = line.expect("read error");
-        if line.contains("Boards with t =") {
-            if !current_board.is_empty() {
-                map.entry(current_t).or_default().push((current_id, current_board.clone()));
-                current_board.clear();
-            }
-            if let Some(v) = line.split('=').nth(1) {
-                current_t = v.trim().split_whitespace().next().unwrap().parse().unwrap();
-            }
-        } else if line.starts_with("Board #") {
-            if !current_board.is_empty() {
-                map.entry(current_t).or_default().push((current_id, current_board.clone()));
-                current_board.clear();
-            }
-            if let Some(v) = line.split('#').nth(1) {
-                current_id = v.split_whitespace().next().unwrap().parse().unwrap();
-            }
-        } else if line.contains('X') || line.contains('.') {
-            let row: Vec<char> = line.chars().filter(|&c| c == 'X' || c == '.').collect();
-            if !row.is_empty() {
-                current_board.push(row);
-            }
-        }
-    }
-    if !current_board.is_empty() {
-        map.entry(current_t).or_default().push((current_id, current_board));
-    }
-    map
-}
+use library_of_2048::{
+    Adversary, canonicalize, classify, expectimax, Alphabet, count_filled, encode_base11,
+    extract_proto_and_tiles, fill_board, id_range, validate_global_id, validate_t,
+    predecessors, validate_local_id, Bitboard, Board, BoardAddress, Direction, Predecessor,
+    GameStatus, History, legal_moves, Recording, ReplayStep, Turn, min_score, move_board_scored, MoveValue, NTupleNetwork, Protoboard, Reachability, RetrogradeTable, Spawn, SpawnModel, BoardSize, max_t, MIN_T,
+};
+use library_of_2048::expectimax::DEFAULT_DEPTH;
+use library_of_2048::local_id::MAX_EXPONENT;
+use library_of_2048::status::DEFAULT_GOAL;
+use library_of_2048::retrograde::MAX_SOLVABLE_SIDE;
 
-fn count_filled(board: &Vec<Vec<char>>) -> usize {
-    board.iter().flatten().filter(|&&c| c == 'X').count()
-}
+// Largest tile the board editor cycles through.
+const EDITOR_MAX_TILE: u32 = 4096;
 
-fn parse_base11(s: &str) -> Result<Vec<u32>, String> {
-    if s.chars().filter(|&c| c == 'B' || c == 'b').count() > 1 {
-        return Err("Invalid base-11 ID: more than one 'B'".to_string());
-    }
-    s.chars().map(|c| match c {
-        '1'..='9' => Ok(c.to_digit(11).unwrap()),
-        'A' | 'a' => Ok(10),
-        'B' | 'b' => Ok(11),
-        _ => Err(format!("Invalid base-11 digit: {}", c)),
-    }).collect()
-}
-
-fn fill_board(proto: &Vec<Vec<char>>, tiles: &[u32]) -> Vec<Vec<u32>> {
-    let mut filled = vec![vec![0; 4]; 4];
-    let mut iter = tiles.iter();
-    for i in 0..4 {
-        for j in 0..4 {
-            if proto[i][j] == 'X' {
-                let exp = *iter.next().unwrap();
-                filled[i][j] = 2u32.pow(exp);
-            }
-        }
-    }
-    filled
+// Largest goal exponent offered for an exact solve. Past 32 a 3x3 solve covers
+// millions of states and can take minutes.
+fn max_retrograde_goal(size: BoardSize) -> u32 {
+    if size.rows * size.cols > 6 { 5 } else { MAX_EXPONENT }
 }
 
 fn tile_color(value: u32) -> egui::Color32 {
@@ -94,101 +38,15 @@ fn tile_color(value: u32) -> egui::Color32 {
         512  => egui::Color32::from_rgb(0xed, 0xc8, 0x50),
         1024 => egui::Color32::from_rgb(0xed, 0xc5, 0x3f),
         2048 => egui::Color32::from_rgb(0xed, 0xc2, 0x2e),
+        4096   => egui::Color32::from_rgb(0x3c, 0x3a, 0x32),
+        8192   => egui::Color32::from_rgb(0x2f, 0x4f, 0x8f),
+        16384  => egui::Color32::from_rgb(0x2a, 0x6f, 0x97),
+        32768  => egui::Color32::from_rgb(0x1f, 0x8a, 0x70),
+        65536  => egui::Color32::from_rgb(0x6a, 0x3d, 0x9a),
+        131072 => egui::Color32::from_rgb(0x9a, 0x1f, 0x40),
         _    => egui::Color32::from_rgb(0xcd, 0xc1, 0xb4), // fallback for higher tiles
     }
 }
-// Search for new board based on move
-fn slide_and_merge_line(mut line: Vec<u32>) -> Vec<u32> {
-    // Remove zeros
-    line.retain(|&x| x != 0);
-    let mut result = Vec::with_capacity(4);
-    let mut skip = false;
-    for i in 0..line.len() {
-        if skip {
-            skip = false;
-            continue;
-        }
-        if i + 1 < line.len() && line[i] == line[i + 1] {
-            result.push(line[i] * 2);
-            skip = true;
-        } else {
-            result.push(line[i]);
-        }
-    }
-    // Pad with zeros
-    while result.len() < 4 {
-        result.push(0);
-    }
-    result
-}
-
-fn move_board(board: &Vec<Vec<u32>>, dir: Direction) -> Vec<Vec<u32>> {
-    let mut new_board = vec![vec![0; 4]; 4];
-    match dir {
-        Direction::Left => {
-            for i in 0..4 {
-                let row = board[i].clone();
-                new_board[i] = slide_and_merge_line(row);
-            }
-        }
-        Direction::Right => {
-            for i in 0..4 {
-                let mut row = board[i].clone();
-                row.reverse();
-                let mut merged = slide_and_merge_line(row);
-                merged.reverse();
-                new_board[i] = merged;
-            }
-        }
-        Direction::Up => {
-            for j in 0..4 {
-                let mut col: Vec<u32> = (0..4).map(|i| board[i][j]).collect();
-                let merged = slide_and_merge_line(col);
-                for i in 0..4 {
-                    new_board[i][j] = merged[i];
-                }
-            }
-        }
-        Direction::Down => {
-            for j in 0..4 {
-                let mut col: Vec<u32> = (0..4).map(|i| board[i][j]).collect();
-                col.reverse();
-                let mut merged = slide_and_merge_line(col);
-                merged.reverse();
-                for i in 0..4 {
-                    new_board[i][j] = merged[i];
-                }
-            }
-        }
-    }
-    new_board
-}
-
-fn extract_proto_and_tiles(board: &Vec<Vec<u32>>) -> (Vec<Vec<char>>, Vec<u32>) {
-    let mut proto = vec![vec!['.'; 4]; 4];
-    let mut tiles = Vec::new();
-    for i in 0..4 {
-        for j in 0..4 {
-            if board[i][j] != 0 {
-                proto[i][j] = 'X';
-                tiles.push(board[i][j].ilog2());
-            }
-        }
-    }
-    (proto, tiles)
-}
-
-fn encode_base11(tiles: &[u32]) -> String {
-    tiles.iter().map(|&v| {
-        match v {
-            1..=9 => std::char::from_digit(v, 10).unwrap(),
-            10 => 'A',
-            11 => 'B',
-            _ => '?'
-        }
-    }).collect()
-}
-
 fn arrow_button(
     ui: &mut egui::Ui,
     enabled: bool,
@@ -207,83 +65,794 @@ fn arrow_button(
 
 impl App2048 {
     fn simulate_and_update(&mut self, dir: Direction) {
+        if self.editor.is_some() {
+            return;
+        }
         if let Some(board) = &self.generated {
-            let mut new_board = move_board(board, dir);
-    
+            let (new_board, points) = move_board_scored(board, dir);
+
             // Only spawn a tile if the move changed the board
             let moved = new_board != *board;
 
-            if self.spawn_tile && moved {
-                // Collect empty cells
-                let mut empty_cells = Vec::new();
-                for i in 0..4 {
-                    for j in 0..4 {
-                        if new_board[i][j] == 0 {
-                            empty_cells.push((i, j));
+            let spawned = match self.spawn_mode {
+                _ if !self.spawn_tile || !moved => None,
+                SpawnMode::Random => self.spawns.spawn(&new_board),
+                SpawnMode::Adversarial => self.adversary.worst_spawn(&new_board),
+                SpawnMode::Manual => {
+                    // Wait for the spawn to be picked on the grid.
+                    self.pending_spawn = Some(PendingSpawn { direction: dir, afterstate: new_board, points, cell: None });
+                    return;
+                }
+            };
+            self.complete_move(dir, new_board, points, spawned);
+        }
+    }
+
+    // Place `spawned` on the afterstate of `dir` and show it as the next board.
+    fn complete_move(&mut self, dir: Direction, afterstate: Board, points: u32, spawned: Option<Spawn>) {
+        let Some(board) = self.generated.clone() else {
+            return;
+        };
+        let new_board = match spawned {
+            Some(spawn) => spawn.apply_to_board(&afterstate),
+            None => afterstate,
+        };
+        // A board shown by the replay viewer is not in the history yet.
+        if self.history.current().is_none_or(|entry| entry.board != board) {
+            self.record(board, self.score, None, None);
+        }
+        let turn = Turn { direction: dir, spawn: spawned };
+        self.show_scored_board(new_board, self.score + points as u64, Some(points), Some(turn));
+        // Only played moves count towards Best, not the score of a board loaded by ID.
+        self.best_score = self.best_score.max(self.score);
+    }
+
+    // Start editing the shown board, or an empty one; stopping shows the edited
+    // board if it is in the library and drops it otherwise.
+    fn set_editing(&mut self, editing: bool) {
+        if editing {
+            let board = self.generated.clone().unwrap_or_else(|| Board::empty(self.size));
+            self.pending_spawn = None;
+            self.view_proto = false;
+            self.editor = Some(board);
+            self.update_editor_ids();
+        } else if let Some(board) = self.editor.take() {
+            if self.editor_error.take().is_none() {
+                self.show_board(board);
+            } else if let Some(board) = self.generated.clone() {
+                // The edit is dropped: point the ID fields back at the board in play.
+                self.display_board(board, self.score, self.last_points);
+            } else {
+                self.selected_t = None;
+                self.current_proto = None;
+                self.filled_tiles = 0;
+                self.global_id.clear();
+                self.local_id.clear();
+                self.address.clear();
+            }
+        }
+    }
+
+    // Left click cycles a cell up through the tiles, right click cycles it down.
+    fn board_editor(&mut self, ui: &mut egui::Ui, grid_rect: egui::Rect, cell_size: f32) {
+        let Some(board) = &mut self.editor else {
+            return;
+        };
+        let mut edited = false;
+        for row in 0..self.size.rows {
+            for col in 0..self.size.cols {
+                let rect = egui::Rect::from_min_size(
+                    grid_rect.min + egui::vec2(col as f32, row as f32) * cell_size,
+                    egui::vec2(cell_size, cell_size),
+                );
+                let response = ui.interact(rect, egui::Id::new(("editor_cell", row, col)), egui::Sense::click());
+                let v = board[row][col];
+                if response.clicked() {
+                    board[row][col] = match v {
+                        0 => 2,
+                        _ if v >= EDITOR_MAX_TILE => 0,
+                        _ => v * 2,
+                    };
+                    edited = true;
+                } else if response.secondary_clicked() {
+                    board[row][col] = match v {
+                        0 => EDITOR_MAX_TILE,
+                        2 => 0,
+                        _ => v / 2,
+                    };
+                    edited = true;
+                }
+                if response.hovered() {
+                    ui.painter().rect_stroke(
+                        rect.shrink(cell_size * 0.03),
+                        cell_size * 0.18,
+                        egui::Stroke::new(2.0, egui::Color32::LIGHT_BLUE),
+                        egui::StrokeKind::Inside,
+                    );
+                }
+            }
+        }
+        if edited {
+            self.update_editor_ids();
+        }
+    }
+
+    // Point the t, global ID and local ID fields at the edited board, checking
+    // them against the library's rules.
+    fn update_editor_ids(&mut self) {
+        let Some(board) = &self.editor else {
+            return;
+        };
+        let (proto, tiles) = extract_proto_and_tiles(board);
+        let t = proto.t();
+        self.current_proto = Some(proto);
+        self.filled_tiles = tiles.len();
+        self.global_id = proto.global_id().map(|gid| gid.to_string()).unwrap_or_default();
+        self.local_id = encode_base11(&tiles);
+        self.global_id_error = None;
+        self.local_id_error = None;
+        let checked = validate_t(self.size, &t.to_string())
+            .and_then(|t| validate_global_id(self.size, t, &self.global_id))
+            .and_then(|_| validate_local_id(t, &self.local_id, self.alphabet));
+        match checked {
+            Ok(_) => {
+                self.selected_t = Some(t);
+                self.address = BoardAddress::from_board(board).map(|a| a.to_string()).unwrap_or_default();
+                self.editor_error = None;
+            }
+            Err(e) => {
+                self.selected_t = self.t_values.contains(&t).then_some(t);
+                self.address.clear();
+                self.editor_error = Some(e.to_string());
+            }
+        }
+    }
+
+    // Let the user click an empty cell of the pending afterstate and pick its tile.
+    fn manual_spawn_picker(&mut self, ui: &mut egui::Ui, grid_rect: egui::Rect, cell_size: f32) {
+        let Some(pending) = &mut self.pending_spawn else {
+            return;
+        };
+        let cell_rect = |row: usize, col: usize| {
+            egui::Rect::from_min_size(
+                grid_rect.min + egui::vec2(col as f32, row as f32) * cell_size,
+                egui::vec2(cell_size, cell_size),
+            )
+        };
+        for (row, col) in pending.afterstate.empty_cells() {
+            let rect = cell_rect(row, col);
+            let response = ui.interact(rect, egui::Id::new(("spawn_cell", row, col)), egui::Sense::click());
+            if response.clicked() {
+                pending.cell = Some((row, col));
+            }
+            if pending.cell == Some((row, col)) || response.hovered() {
+                ui.painter().rect_stroke(
+                    rect.shrink(cell_size * 0.03),
+                    cell_size * 0.18,
+                    egui::Stroke::new(2.0, egui::Color32::YELLOW),
+                    egui::StrokeKind::Inside,
+                );
+            }
+        }
+        let Some((row, col)) = pending.cell else {
+            return;
+        };
+        let rect = cell_rect(row, col);
+        let mut chosen = None;
+        for (exponent, offset) in [(1, -0.22), (2, 0.22)] {
+            let button_rect = egui::Rect::from_center_size(
+                rect.center() + egui::vec2(offset * cell_size, 0.0),
+                egui::vec2(cell_size * 0.36, cell_size * 0.36),
+            );
+            if ui.put(button_rect, egui::Button::new((1u32 << exponent).to_string())).clicked() {
+                chosen = Some(Spawn { row, col, exponent });
+            }
+        }
+        if let Some(spawn) = chosen
+            && let Some(pending) = self.pending_spawn.take()
+        {
+            self.complete_move(pending.direction, pending.afterstate, pending.points, Some(spawn));
+        }
+    }
+
+    // A search can take a second or more, so it runs on demand on a worker thread
+    // and the badge shows its verdict once it arrives.
+    fn reachability_badge(&mut self, ui: &mut egui::Ui) {
+        let Some(board) = self.generated.as_ref().and_then(Bitboard::from_board) else {
+            return;
+        };
+        if let Some((pending, receiver)) = &self.reachability_job {
+            match receiver.try_recv() {
+                Ok(verdict) => {
+                    self.reachability_cache = Some((*pending, verdict));
+                    self.reachability_job = None;
+                }
+                Err(TryRecvError::Empty) if *pending == board => {
+                    ui.spinner();
+                    ui.label("Checking reachability…");
+                    ui.ctx().request_repaint_after(Duration::from_millis(100));
+                    return;
+                }
+                // The board changed or the worker died: drop the search.
+                Err(_) => self.reachability_job = None,
+            }
+        }
+        if self.reachability_cache.as_ref().is_none_or(|(cached, _)| *cached != board) {
+            if ui.button("Check reachability").clicked() {
+                let (sender, receiver) = mpsc::channel();
+                // The receiver is gone if the board changed first; nothing to report then.
+                thread::spawn(move || { let _ = sender.send(classify(board)); });
+                self.reachability_job = Some((board, receiver));
+            }
+            return;
+        }
+        let Some((_, verdict)) = &self.reachability_cache else {
+            return;
+        };
+        match verdict {
+            Reachability::Reachable(proof) => {
+                let path: Vec<String> = proof
+                    .boards()
+                    .iter()
+                    .filter_map(|b| b.address())
+                    .map(|a| a.to_string())
+                    .collect();
+                ui.colored_label(egui::Color32::GREEN, format!("Reachable ({} moves)", proof.steps.len()))
+                    .on_hover_text(path.join(" → "));
+            }
+            Reachability::Unreachable(reason) => {
+                ui.colored_label(egui::Color32::RED, "Unreachable").on_hover_text(reason.to_string());
+            }
+            Reachability::Unknown { explored } => {
+                ui.colored_label(egui::Color32::GRAY, "Reachability unknown")
+                    .on_hover_text(format!("Gave up after {} positions", explored));
+            }
+        }
+    }
+
+    fn predecessors_panel(&mut self, ui: &mut egui::Ui) {
+        let Some(board) = self.generated.as_ref().and_then(Bitboard::from_board) else {
+            ui.label("Generate a board to list its predecessors.");
+            return;
+        };
+        if self.predecessor_cache.as_ref().is_none_or(|(cached, _)| *cached != board) {
+            self.predecessor_cache = Some((board, predecessors(board)));
+        }
+        let mut jump_to = None;
+        if let Some((_, found)) = &self.predecessor_cache {
+            ui.heading(format!("Predecessors ({})", found.len()));
+            let row_height = ui.text_style_height(&egui::TextStyle::Body);
+            egui::ScrollArea::vertical().show_rows(ui, row_height, found.len(), |ui, range| {
+                for p in &found[range] {
+                    let spawn = format!(
+                        "{:?} + {} at ({}, {})",
+                        p.direction, p.spawn.value(), p.spawn.row, p.spawn.col
+                    );
+                    match p.board.address() {
+                        Some(address) => {
+                            if ui.selectable_label(false, format!("{}  {}", address, spawn)).clicked() {
+                                jump_to = Some(p.board.to_board());
+                            }
+                        }
+                        None => {
+                            ui.label(format!("(outside library)  {}", spawn));
                         }
                     }
                 }
-                if !empty_cells.is_empty() {
-                    let mut rng = rand::rng();
-                    let &(i, j) = empty_cells.choose(&mut rng).unwrap();
-                    // 90% chance for 2, 10% for 4
-                    let value = if rng.random_range(0..10) == 0 { 4 } else { 2 };
-                    new_board[i][j] = value;
+            });
+        }
+        if let Some(board) = jump_to {
+            self.show_board(board);
+        }
+    }
+
+    // Switch board dimensions, clearing everything tied to the old size.
+    fn set_size(&mut self, size: BoardSize) {
+        self.size = size;
+        self.t_values = (MIN_T..=max_t(size)).collect();
+        self.selected_t = None;
+        self.current_proto = None;
+        self.generated = None;
+        self.pending_spawn = None;
+        self.view_proto = false;
+        self.global_id.clear();
+        self.local_id.clear();
+        self.global_id_error = None;
+        self.local_id_error = None;
+        if self.editor.is_some() {
+            self.editor = Some(Board::empty(size));
+            self.update_editor_ids();
+        }
+    }
+
+    // Point every ID field at `board` and record it. A board loaded by ID scores
+    // the least its tiles could have earned.
+    fn show_board(&mut self, board: Board) {
+        let score = min_score(&board);
+        self.show_scored_board(board, score, None, None);
+    }
+
+    fn show_scored_board(&mut self, board: Board, score: u64, last_points: Option<u32>, turn: Option<Turn>) {
+        self.record(board.clone(), score, last_points, turn);
+        self.display_board(board, score, last_points);
+    }
+
+    // Push `board` onto the history unless it is already the current entry.
+    fn record(&mut self, board: Board, score: u64, last_points: Option<u32>, turn: Option<Turn>) {
+        if self.history.current().is_some_and(|entry| entry.board == board) {
+            return;
+        }
+        let address = BoardAddress::from_board(&board).map_or_else(|| "outside library".to_string(), |a| a.to_string());
+        self.history.push(HistoryEntry { board, address, score, last_points, turn });
+    }
+
+    // The turns leading to the current board, back to the last board loaded by ID,
+    // or why they cannot be saved. None before any board is shown.
+    fn current_recording(&self) -> Option<Result<Recording, String>> {
+        let current = self.history.current_index()?;
+        let entries = &self.history.entries()[..=current];
+        let start = entries.iter().rposition(|entry| entry.turn.is_none()).unwrap_or(0);
+        let start_address = match BoardAddress::try_from_board(&entries[start].board) {
+            Ok(address) => address,
+            Err(e) => return Some(Err(format!("Cannot save: the start board is outside the library: {}", e))),
+        };
+        let mut recording = Recording::new(start_address);
+        recording.turns.extend(entries[start + 1..].iter().filter_map(|entry| entry.turn));
+        Some(Ok(recording))
+    }
+
+    // Save the current session, or load a recording and step through it.
+    fn recording_controls(&mut self, ui: &mut egui::Ui) {
+        ui.label("Recording:");
+        ui.add(egui::TextEdit::singleline(&mut self.recording_path).hint_text("path to a recording file"));
+        let recording = self.current_recording();
+        if ui.add_enabled(recording.is_some(), egui::Button::new("Save")).clicked()
+            && let Some(recording) = recording
+        {
+            self.recording_status = Some(recording.and_then(|recording| {
+                match fs::write(&self.recording_path, recording.to_string()) {
+                    Ok(()) => Ok(format!("Saved {} turns", recording.turns.len())),
+                    Err(e) => Err(format!("Could not save recording: {}", e)),
+                }
+            }));
+        }
+        if ui.button("Load replay").clicked() {
+            let loaded = fs::read_to_string(&self.recording_path)
+                .map_err(|e| e.to_string())
+                .and_then(|text| text.parse::<Recording>())
+                .and_then(|recording| recording.replay());
+            match loaded {
+                Ok(steps) => {
+                    self.recording_status = Some(Ok(format!("Loaded {} turns", steps.len() - 1)));
+                    self.replay = Some(Replay { steps, position: 0 });
+                    self.show_replay_step();
                 }
+                Err(e) => self.recording_status = Some(Err(format!("Could not load recording: {}", e))),
+            }
+        }
+        if let Some(replay) = &mut self.replay {
+            let last = replay.steps.len() - 1;
+            let before = replay.position;
+            if ui.add_enabled(replay.position > 0, egui::Button::new("◀")).clicked() {
+                replay.position -= 1;
+            }
+            ui.add(egui::Slider::new(&mut replay.position, 0..=last).text(format!("of {}", last)));
+            if ui.add_enabled(replay.position < last, egui::Button::new("▶")).clicked() {
+                replay.position += 1;
             }
-    
-            let (new_proto, new_tiles) = extract_proto_and_tiles(&new_board);
-            let t = new_tiles.len() as u32;
-            if let Some(boards) = self.protoboards.get(&t) {
-                if let Some((gid, proto)) = boards.iter().find(|(_, p)| p == &new_proto) {
-                    self.selected_t = Some(t);
-                    self.global_id = gid.to_string();
-                    self.local_id = encode_base11(&new_tiles);
-                    self.current_proto = Some(new_proto);
-                    self.filled_tiles = new_tiles.len();
-                    self.generated = Some(new_board);
-                    self.view_proto = false;
-                    self.global_id_error = None;
-                    self.local_id_error = None;
+            let changed = replay.position != before;
+            if ui.button("Close replay").clicked() {
+                self.replay = None;
+            } else if changed {
+                self.show_replay_step();
+            }
+        }
+        match &self.recording_status {
+            Some(Ok(msg)) => {
+                ui.label(msg);
+            }
+            Some(Err(msg)) => {
+                ui.colored_label(egui::Color32::RED, msg);
+            }
+            None => {}
+        }
+    }
+
+    // Show the replay's current board without adding it to the history.
+    fn show_replay_step(&mut self) {
+        let Some(step) = self.replay.as_ref().map(|replay| replay.steps[replay.position].clone()) else {
+            return;
+        };
+        self.display_board(step.board, step.score, None);
+    }
+
+    // Restore the history's current entry without recording it again.
+    fn restore_history(&mut self) {
+        if let Some(entry) = self.history.current().cloned() {
+            self.display_board(entry.board, entry.score, entry.last_points);
+        }
+    }
+
+    fn undo(&mut self) {
+        if self.history.undo().is_some() {
+            self.restore_history();
+        }
+    }
+
+    fn redo(&mut self) {
+        if self.history.redo().is_some() {
+            self.restore_history();
+        }
+    }
+
+    fn history_panel(&mut self, ui: &mut egui::Ui) {
+        ui.heading("History");
+        ui.horizontal(|ui| {
+            if ui.add_enabled(self.history.can_undo(), egui::Button::new("Undo")).on_hover_text("Ctrl+Z").clicked() {
+                self.undo();
+            }
+            if ui.add_enabled(self.history.can_redo(), egui::Button::new("Redo")).on_hover_text("Ctrl+Y").clicked() {
+                self.redo();
+            }
+        });
+        let mut jump_to = None;
+        let current = self.history.current_index();
+        let row_height = ui.text_style_height(&egui::TextStyle::Body);
+        let entries = self.history.entries();
+        egui::ScrollArea::vertical().show_rows(ui, row_height, entries.len(), |ui, range| {
+            for i in range {
+                let label = format!("{}. {}  ({})", i + 1, entries[i].address, entries[i].score);
+                if ui.selectable_label(current == Some(i), label).clicked() {
+                    jump_to = Some(i);
                 }
             }
+        });
+        if let Some(i) = jump_to
+            && self.history.jump(i).is_some()
+        {
+            self.restore_history();
+        }
+    }
+
+    // Show `board` and point every ID field at it. A board outside the library,
+    // e.g. one holding two 2048s, is still shown and playable, with the local ID
+    // cleared and the reason in the address error.
+    fn display_board(&mut self, board: Board, score: u64, last_points: Option<u32>) {
+        if board.size() != self.size {
+            self.set_size(board.size());
+        }
+        match BoardAddress::try_from_board(&board) {
+            Ok(address) => {
+                self.selected_t = Some(address.t);
+                self.global_id = address.global_id.to_string();
+                self.local_id = address.local_id.to_string();
+                self.current_proto = Some(address.protoboard());
+                self.filled_tiles = address.local_id.len();
+                self.address = address.to_string();
+                self.address_error = None;
+            }
+            Err(e) => {
+                // Keep the protoboard when there is one, so the move controls stay.
+                let (proto, _) = extract_proto_and_tiles(&board);
+                let gid = proto.global_id();
+                self.selected_t = gid.map(|_| proto.t());
+                self.global_id = gid.map_or(String::new(), |g| g.to_string());
+                self.local_id.clear();
+                self.current_proto = gid.map(|_| proto);
+                self.filled_tiles = proto.t() as usize;
+                self.address.clear();
+                self.address_error = Some(format!("This board is outside the library: {}", e));
+            }
+        }
+        // Re-arm the win overlay once the board no longer holds the goal tile.
+        if !matches!(GameStatus::of(&board, DEFAULT_GOAL), GameStatus::Won { .. }) {
+            self.keep_going = false;
+        }
+        self.score = score;
+        self.last_points = last_points;
+        self.generated = Some(board);
+        self.view_proto = false;
+        self.global_id_error = None;
+        self.local_id_error = None;
+        self.move_values = None;
+        self.ntuple_suggestion = None;
+        self.pending_spawn = None;
+        self.editor = None;
+        self.editor_error = None;
+    }
+
+    // How tiles spawn: randomly with a seed and tile distribution, by the
+    // adversary, or by hand. Applying restarts the generator.
+    fn spawn_controls(&mut self, ui: &mut egui::Ui) {
+        ui.radio_value(&mut self.spawn_mode, SpawnMode::Random, "Random");
+        ui.radio_value(&mut self.spawn_mode, SpawnMode::Adversarial, "Adversarial")
+            .on_hover_text("Spawn the tile that leaves the player worst off");
+        ui.radio_value(&mut self.spawn_mode, SpawnMode::Manual, "Manual")
+            .on_hover_text("After each move, click an empty cell and pick 2 or 4");
+        if self.spawn_mode != SpawnMode::Manual {
+            self.pending_spawn = None;
+        }
+        match self.spawn_mode {
+            SpawnMode::Random => {}
+            SpawnMode::Adversarial => return,
+            SpawnMode::Manual => {
+                if self.pending_spawn.is_some() {
+                    ui.label("Click an empty cell to place the spawn");
+                }
+                return;
+            }
+        }
+        ui.label("Seed:");
+        // Typed rather than dragged: egui's DragValue goes through f64 and would
+        // round seeds above 2^53.
+        ui.add(egui::TextEdit::singleline(&mut self.spawn_seed).desired_width(150.0));
+        ui.label("Tiles:");
+        ui.add(egui::TextEdit::singleline(&mut self.spawn_distribution).hint_text("2:0.9 4:0.1").desired_width(120.0));
+        if ui.button("Classic").clicked() {
+            self.spawn_distribution = SpawnModel::classic(0).to_string();
+        }
+        if ui.button("4 only").clicked() {
+            self.spawn_distribution = SpawnModel::fours_only(0).to_string();
+        }
+        if ui.button("Apply").on_hover_text("Use these tiles and restart the spawns from the seed").clicked() {
+            let parsed = self
+                .spawn_seed
+                .trim()
+                .parse::<u64>()
+                .map_err(|_| format!("Invalid seed '{}'! Expected a whole number up to {}", self.spawn_seed.trim(), u64::MAX))
+                .and_then(|seed| SpawnModel::parse(&self.spawn_distribution, seed));
+            match parsed {
+                Ok(spawns) => {
+                    self.spawn_distribution = spawns.to_string();
+                    self.spawns = spawns;
+                    self.spawn_error = None;
+                }
+                Err(e) => self.spawn_error = Some(e),
+            }
+        }
+        if let Some(ref msg) = self.spawn_error {
+            ui.colored_label(egui::Color32::RED, msg);
+        } else {
+            ui.label(format!("Using {} from seed {}", self.spawns, self.spawns.seed()));
+        }
+    }
+
+    // Load trained n-tuple weights, then suggest or play the network's greedy move.
+    fn ntuple_controls(&mut self, ui: &mut egui::Ui) {
+        ui.label("N-tuple weights:");
+        ui.add(egui::TextEdit::singleline(&mut self.ntuple_path).hint_text("path to a weights file"));
+        if ui.button("Load").clicked() {
+            match NTupleNetwork::load(&self.ntuple_path) {
+                Ok(network) => {
+                    self.ntuple = Some(network);
+                    self.ntuple_error = None;
+                }
+                Err(e) => {
+                    self.ntuple = None;
+                    self.ntuple_error = Some(format!("Could not load weights: {}", e));
+                }
+            }
+        }
+        let board = self.generated.as_ref().and_then(Bitboard::from_board);
+        let best = self.ntuple.as_ref().zip(board).and_then(|(network, board)| network.best_afterstate(board));
+        if ui.add_enabled(best.is_some(), egui::Button::new("Suggest")).clicked()
+            && let Some(best) = best
+        {
+            self.ntuple_suggestion = Some(format!("Suggested move: {:?}", best.direction));
+        }
+        if ui.add_enabled(best.is_some(), egui::Button::new("Play")).clicked()
+            && let Some(best) = best
+        {
+            self.simulate_and_update(best.direction);
+        }
+        if let Some(ref msg) = self.ntuple_error {
+            ui.colored_label(egui::Color32::RED, msg);
+        } else if let Some(ref msg) = self.ntuple_suggestion {
+            ui.label(msg);
+        }
+    }
+
+    // Solve a small board exactly on a worker thread and show the current board's
+    // optimal win probability.
+    fn retrograde_controls(&mut self, ui: &mut egui::Ui) {
+        if let Some(receiver) = &self.retrograde_job {
+            match receiver.try_recv() {
+                Ok(Ok(table)) => {
+                    self.retrograde = Some(table);
+                    self.retrograde_error = None;
+                    self.retrograde_job = None;
+                }
+                Ok(Err(e)) => {
+                    self.retrograde = None;
+                    self.retrograde_error = Some(e);
+                    self.retrograde_job = None;
+                }
+                Err(TryRecvError::Empty) => {}
+                Err(TryRecvError::Disconnected) => {
+                    self.retrograde_error = Some("The solver stopped unexpectedly".to_string());
+                    self.retrograde_job = None;
+                }
+            }
+        }
+        ui.label("Exact solve, goal tile:");
+        let max_goal = max_retrograde_goal(self.size);
+        let mut goal = self.retrograde_goal.min(max_goal);
+        egui::ComboBox::from_id_salt("retrograde_goal")
+            .selected_text((1u32 << goal).to_string())
+            .show_ui(ui, |ui| {
+                for exponent in 2..=max_goal {
+                    ui.selectable_value(&mut goal, exponent, (1u32 << exponent).to_string());
+                }
+            });
+        self.retrograde_goal = goal;
+        let solved = self.retrograde.as_ref().is_some_and(|table| table.size() == self.size && table.goal() == goal);
+        if self.retrograde_job.is_some() {
+            ui.spinner();
+            ui.label("Solving…");
+            ui.ctx().request_repaint_after(Duration::from_millis(100));
+        } else if ui.add_enabled(!solved, egui::Button::new("Solve")).on_hover_text("May take a few seconds on 3x3").clicked() {
+            let (sender, receiver) = mpsc::channel();
+            let size = self.size;
+            // The receiver is gone if the app was reset first; nothing to report then.
+            thread::spawn(move || { let _ = sender.send(RetrogradeTable::solve(size, goal)); });
+            self.retrograde_job = Some(receiver);
+        }
+        if let Some(ref msg) = self.retrograde_error {
+            ui.colored_label(egui::Color32::RED, msg);
+        }
+        let Some(table) = self.retrograde.as_ref().filter(|table| table.size() == self.size && table.goal() == goal) else {
+            return;
+        };
+        match self.generated.as_ref().map(|board| table.win_probability(board)) {
+            Some(Some(p)) => ui.label(format!("Win probability: {:.6}", p)),
+            Some(None) => ui.label("Not reachable from a new game"),
+            None => ui.label(format!("{} states solved", table.len())),
+        };
+    }
+
+    // Dim the grid and announce a win or loss; a win can be dismissed to keep playing.
+    fn status_overlay(&mut self, ui: &mut egui::Ui, grid_rect: egui::Rect) {
+        let Some(board) = &self.generated else {
+            return;
+        };
+        let status = GameStatus::of(board, DEFAULT_GOAL);
+        let message = match status {
+            GameStatus::Ongoing => return,
+            GameStatus::Won { .. } if self.keep_going => return,
+            GameStatus::Won { tile } => format!("You win! Reached {}", tile),
+            GameStatus::Lost => "Game over: no moves left".to_string(),
+        };
+        ui.painter().rect_filled(grid_rect, 0.0, egui::Color32::from_black_alpha(160));
+        ui.painter().text(
+            grid_rect.center(),
+            egui::Align2::CENTER_CENTER,
+            message,
+            egui::FontId::proportional(grid_rect.height() * 0.07),
+            egui::Color32::WHITE,
+        );
+        let button_rect = egui::Rect::from_center_size(
+            grid_rect.center() + egui::vec2(0.0, grid_rect.height() * 0.12),
+            egui::vec2(120.0, 32.0),
+        );
+        if let GameStatus::Won { .. } = status
+            && ui.put(button_rect, egui::Button::new("Keep going")).clicked()
+        {
+            self.keep_going = true;
         }
     }
-}
 
-#[derive(Copy, Clone)]
-enum Direction {
-    Up,
-    Down,
-    Left,
-    Right,
+    // Play the expectimax choice and show what every direction was worth.
+    fn best_move_button(&mut self, ui: &mut egui::Ui) {
+        let board = self.generated.as_ref().and_then(Bitboard::from_board);
+        let clicked = ui
+            .add_enabled(board.is_some(), egui::Button::new("Best move"))
+            .on_hover_text(format!("Expectimax search, {} plies deep, assuming classic spawns (4x4 only)", DEFAULT_DEPTH))
+            .clicked();
+        if clicked && let Some(board) = board {
+            let values = expectimax(board, DEFAULT_DEPTH);
+            if let Some(best) = values.iter().max_by(|a, b| a.value.total_cmp(&b.value)) {
+                self.simulate_and_update(best.direction);
+            }
+            self.move_values = Some(values);
+        }
+        if let Some(values) = &self.move_values {
+            let text: Vec<String> = values.iter().map(|v| format!("{:?}: {:.0}", v.direction, v.value)).collect();
+            ui.label(if text.is_empty() { "No legal moves".to_string() } else { text.join(", ") });
+        }
+    }
 }
 
 struct App2048 {
-    protoboards: HashMap<u32, Vec<(usize, Vec<Vec<char>>) >>,
+    size: BoardSize,
     t_values: Vec<u32>,
     selected_t: Option<u32>,
     global_id: String,
     local_id: String,
-    current_proto: Option<Vec<Vec<char>>>,
+    current_proto: Option<Protoboard>,
     filled_tiles: usize,
-    generated: Option<Vec<Vec<u32>>>,
+    generated: Option<Board>,
     view_proto: bool,
     focus_global_id: bool,
     focus_local_id: bool,
     global_id_error: Option<String>,
     local_id_error: Option<String>,
+    address: String,
+    address_error: Option<String>,
     spawn_tile: bool,
+    spawns: SpawnModel,
+    spawn_seed: String,
+    spawn_distribution: String,
+    spawn_error: Option<String>,
+    spawn_mode: SpawnMode,
+    adversary: Adversary,
+    pending_spawn: Option<PendingSpawn>,
+    editor: Option<Board>,
+    editor_error: Option<String>,
+    show_canonical: bool,
+    alphabet: Alphabet,
+    show_predecessors: bool,
+    predecessor_cache: Option<(Bitboard, Vec<Predecessor>)>,
+    reachability_cache: Option<(Bitboard, Reachability)>,
+    reachability_job: Option<(Bitboard, Receiver<Reachability>)>,
+    move_values: Option<Vec<MoveValue>>,
+    ntuple_path: String,
+    ntuple: Option<NTupleNetwork>,
+    ntuple_error: Option<String>,
+    ntuple_suggestion: Option<String>,
+    retrograde_goal: u32,
+    retrograde: Option<RetrogradeTable>,
+    retrograde_error: Option<String>,
+    retrograde_job: Option<Receiver<Result<RetrogradeTable, String>>>,
+    keep_going: bool,
+    score: u64,
+    last_points: Option<u32>,
+    best_score: u64,
+    history: History<HistoryEntry>,
+    show_history: bool,
+    recording_path: String,
+    recording_status: Option<Result<String, String>>,
+    replay: Option<Replay>,
+}
+
+// A board as it was shown, with the score it had then.
+#[derive(Clone)]
+struct HistoryEntry {
+    board: Board,
+    address: String,
+    score: u64,
+    last_points: Option<u32>,
+    // The move that led here from the previous entry; None for boards loaded by ID.
+    turn: Option<Turn>,
+}
+
+// Where the tile after each move comes from.
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum SpawnMode {
+    Random,
+    Adversarial,
+    Manual,
+}
+
+// A move waiting for its spawn to be placed by hand.
+struct PendingSpawn {
+    direction: Direction,
+    afterstate: Board,
+    points: u32,
+    cell: Option<(usize, usize)>,
+}
+
+// A loaded recording and the step being shown.
+struct Replay {
+    steps: Vec<ReplayStep>,
+    position: usize,
 }
 
 impl Default for App2048 {
     fn default() -> Self {
-        let protoboards = parse_protoboards("protoboards.txt");
-        let mut t_values: Vec<_> = protoboards.keys().cloned().collect();
-        t_values.sort();
+        let spawns = SpawnModel::classic(rand::random());
         App2048 {
-            protoboards,
-            t_values,
+            size: BoardSize::CLASSIC,
+            t_values: (MIN_T..=max_t(BoardSize::CLASSIC)).collect(),
             selected_t: None,
             global_id: String::new(),
             local_id: String::new(),
@@ -295,7 +864,42 @@ impl Default for App2048 {
             focus_local_id: false,
             global_id_error: None,
             local_id_error: None,
+            address: String::new(),
+            address_error: None,
             spawn_tile: false,
+            spawn_seed: spawns.seed().to_string(),
+            spawn_distribution: spawns.to_string(),
+            spawns,
+            spawn_error: None,
+            spawn_mode: SpawnMode::Random,
+            adversary: Adversary::default(),
+            pending_spawn: None,
+            editor: None,
+            editor_error: None,
+            show_canonical: false,
+            alphabet: Alphabet::Standard,
+            show_predecessors: false,
+            predecessor_cache: None,
+            reachability_cache: None,
+            reachability_job: None,
+            move_values: None,
+            ntuple_path: String::new(),
+            ntuple: None,
+            ntuple_error: None,
+            ntuple_suggestion: None,
+            retrograde_goal: 5,
+            retrograde: None,
+            retrograde_error: None,
+            retrograde_job: None,
+            keep_going: false,
+            score: 0,
+            last_points: None,
+            best_score: 0,
+            history: History::new(),
+            show_history: false,
+            recording_path: String::new(),
+            recording_status: None,
+            replay: None,
         }
     }
 }
@@ -304,42 +908,70 @@ impl App for App2048 {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut Frame) {
         egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
             ui.horizontal(|ui| {
+                ui.label("Size:");
+                let mut size = self.size;
+                egui::ComboBox::from_id_salt("board_size")
+                    .selected_text(size.to_string())
+                    .show_ui(ui, |ui| {
+                        for option in BoardSize::all() {
+                            ui.selectable_value(&mut size, option, option.to_string());
+                        }
+                    });
+                if size != self.size {
+                    self.set_size(size);
+                }
                 ui.label("Select t:");
                 for &t in &self.t_values {
                     if ui.selectable_label(self.selected_t == Some(t), t.to_string()).clicked() {
                         self.selected_t = Some(t);
                         self.current_proto = None;
                         self.generated = None;
+                        self.pending_spawn = None;
                         self.view_proto = false;
                         self.global_id.clear();
                         self.local_id.clear();
 
-                        // Compute start_id for this t (we could avoid calculating this twice but this was easier than refactoring)
-                        let mut start_id = 1;
-                        for &k in &self.t_values {
-                            if k == t { break; }
-                            start_id += self.protoboards[&k].len();
-                        }
+                        let (start_id, _) = id_range(self.size, t);
                         self.global_id = start_id.to_string();
                         self.focus_global_id = true;
                     }
                 }
                 ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                     if ui.button("Reset").on_hover_text("Reset (R)").clicked()
-                        || ui.input(|i| i.key_pressed(egui::Key::R))
+                        || (!ctx.wants_keyboard_input() && ui.input(|i| i.key_pressed(egui::Key::R)))
                     {
-                        *self = App2048::default();
+                        *self = App2048 { best_score: self.best_score, ..App2048::default() };
                     }
                 });
             });
-            if let Some(t) = self.selected_t {
-                let boards = &self.protoboards[&t];
-                let mut start_id = 1;
-                for &k in &self.t_values {
-                    if k == t { break; }
-                    start_id += self.protoboards[&k].len();
+            ui.horizontal(|ui| {
+                ui.label("Address:");
+                let response = ui.add(
+                    egui::TextEdit::singleline(&mut self.address).hint_text("t:global:local, e.g. 2:119:AA"),
+                );
+                let enter_pressed = response.lost_focus()
+                && ui.ctx().input(|i| i.key_pressed(egui::Key::Enter));
+                if ui.button("Go").clicked() || enter_pressed {
+                    match self.address.parse::<BoardAddress>() {
+                        Ok(address) => self.show_board(address.to_board()),
+                        Err(e) => self.address_error = Some(e.to_string()),
+                    }
+                }
+                if let Some(ref msg) = self.address_error {
+                    ui.colored_label(egui::Color32::RED, msg);
                 }
-                let end_id = start_id + boards.len() - 1;
+                if self.show_canonical
+                    && let Some(canonical) = self.generated.as_ref().and_then(Bitboard::from_board).and_then(canonicalize)
+                {
+                    ui.label(format!(
+                        "Canonical ID: {} ({}, orbit size {})",
+                        canonical.address, canonical.transform, canonical.orbit_size
+                    ));
+                }
+                self.reachability_badge(ui);
+            });
+            if let Some(t) = self.selected_t {
+                let (start_id, end_id) = id_range(self.size, t);
                 ui.label(format!("Valid IDs for t={}: {}..={}", t, start_id, end_id));
                 ui.horizontal(|ui| {
                     ui.label("Global ID:");
@@ -363,30 +995,13 @@ impl App for App2048 {
                     && ui.ctx().input(|i| i.key_pressed(egui::Key::Enter));
 
                     if ui.button("Load Protoboard").clicked() || enter_pressed {
-                        let input = self.global_id.trim();
-                        match input.parse::<usize>() {
+                        match validate_global_id(self.size, t, &self.global_id) {
                             Ok(gid) => {
-                                // Compute valid range for t
-                                let mut start_id = 1;
-                                for &k in &self.t_values {
-                                    if k == t { break; }
-                                    start_id += self.protoboards[&k].len();
-                                }
-                                let end_id = start_id + boards.len() - 1;
-                                if gid < start_id {
-                                    self.global_id_error = Some(format!(
-                                        "Invalid ID! {} is less than minimum {} in range for t={}",
-                                        gid, start_id, t
-                                    ));
-                                } else if gid > end_id {
-                                    self.global_id_error = Some(format!(
-                                        "Invalid ID! {} is greater than maximum {} in range for t={}",
-                                        gid, end_id, t
-                                    ));
-                                } else if let Some((_, proto)) = boards.iter().find(|(id,_)| *id == gid) {
-                                    self.current_proto = Some(proto.clone());
-                                    self.filled_tiles = count_filled(proto);
+                                if let Some(proto) = Protoboard::from_global_id(self.size, gid) {
+                                    self.current_proto = Some(proto);
+                                    self.filled_tiles = count_filled(&proto);
                                     self.generated = None;
+                                    self.pending_spawn = None;
                                     self.view_proto = true;
                                     self.local_id.clear();
                                     self.global_id_error = None;
@@ -397,8 +1012,8 @@ impl App for App2048 {
                                     self.global_id_error = Some("Unknown error loading protoboard.".to_string());
                                 }
                             }
-                            Err(_) => {
-                                self.global_id_error = Some("Invalid ID! Non-integer value.".to_string());
+                            Err(e) => {
+                                self.global_id_error = Some(e.to_string());
                             }
                         }
                     }
@@ -407,9 +1022,12 @@ impl App for App2048 {
                     }
                 });
                 if let Some(proto) = &self.current_proto {
-                    let proto = proto.clone(); // clone proto to avoid borrowing self
-                    let filled_tiles = self.filled_tiles;
-                    ui.label(format!("Local ID length == t={}; Must use digits [1,2,3,4,5,6,7,8,9,A,B]", t));
+                    let proto = *proto; // copy proto to avoid borrowing self
+                    let digits = match self.alphabet {
+                        Alphabet::Standard => "[1,2,3,4,5,6,7,8,9,A,B]",
+                        Alphabet::Extended => "[1,2,3,4,5,6,7,8,9,A,B,C,D,E,F,G,H]",
+                    };
+                    ui.label(format!("Local ID length == t={}; Must use digits {}", t, digits));
                     ui.horizontal(|ui| {
                         ui.label("Local ID:");
                         let response = ui.text_edit_singleline(&mut self.local_id);
@@ -420,28 +1038,12 @@ impl App for App2048 {
                         let enter_pressed = response.lost_focus()
                         && ui.ctx().input(|i| i.key_pressed(egui::Key::Enter));
                         if ui.button("Generate").clicked() || enter_pressed {
-                            // 1. Check length
-                            if self.local_id.len() != filled_tiles {
-                                self.local_id_error = Some(format!(
-                                    "Local ID must be exactly {} characters for t={}.",
-                                    filled_tiles, t
-                                ));
-                            } else if !self.local_id.chars().all(|c| matches!(c, '1'..='9' | 'A' | 'a' | 'B' | 'b')) {
-                                // 2. Check for invalid characters
-                                self.local_id_error = Some(
-                                    "Local ID must only use digits 1-9, A, or B (base-11).".to_string()
-                                );
-                            } else {
-                                // 3. Parse and check for "no more than one B"
-                                match parse_base11(&self.local_id) {
-                                    Ok(tiles) => {
-                                        self.generated = Some(fill_board(&proto, &tiles));
-                                        self.view_proto = false;
-                                        self.local_id_error = None;
-                                    }
-                                    Err(e) => {
-                                        self.local_id_error = Some(e);
-                                    }
+                            match validate_local_id(t, &self.local_id, self.alphabet) {
+                                Ok(lid) => {
+                                    self.show_board(fill_board(&proto, lid.tiles()));
+                                }
+                                Err(e) => {
+                                    self.local_id_error = Some(e.to_string());
                                 }
                             }
                         }
@@ -449,7 +1051,7 @@ impl App for App2048 {
                             ui.colored_label(egui::Color32::RED, msg);
                         }
 
-                        if let Some(board) = &self.generated {
+                        if self.generated.is_some() {
                             // Keyboard input
                             if ctx.input(|i| i.key_pressed(egui::Key::ArrowUp)) {
                                 self.simulate_and_update(Direction::Up);
@@ -467,7 +1069,7 @@ impl App for App2048 {
                             let button_size = egui::vec2(32.0, 32.0);
                             let spacing = 8.0;
                             let row_width = button_size.x * 3.0 + spacing * 2.0;
-                            let enabled = self.generated.is_some();
+                            let legal = self.generated.as_ref().map(legal_moves).unwrap_or_default();
                             let mut do_move = |dir| self.simulate_and_update(dir);
 
                             ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
@@ -478,16 +1080,16 @@ impl App for App2048 {
                                         ui.horizontal(|ui| {
                                             // Add space for one button and one spacing to reach the center of the down button
                                             ui.add_space((5.0 * button_size.x) / 4.0 + spacing);
-                                            arrow_button(ui, enabled, Direction::Up, "↑", &mut do_move);
+                                            arrow_button(ui, legal.contains(&Direction::Up), Direction::Up, "↑", &mut do_move);
                                         });
                             
                                         // Bottom row: Left, Down, Right
                                         ui.horizontal(|ui| {
-                                            arrow_button(ui, enabled, Direction::Left, "←", &mut do_move);
+                                            arrow_button(ui, legal.contains(&Direction::Left), Direction::Left, "←", &mut do_move);
                                             ui.add_space(spacing);
-                                            arrow_button(ui, enabled, Direction::Down, "↓", &mut do_move);
+                                            arrow_button(ui, legal.contains(&Direction::Down), Direction::Down, "↓", &mut do_move);
                                             ui.add_space(spacing);
-                                            arrow_button(ui, enabled, Direction::Right, "→", &mut do_move);
+                                            arrow_button(ui, legal.contains(&Direction::Right), Direction::Right, "→", &mut do_move);
                                         });
                                     });
                                 });
@@ -541,23 +1143,90 @@ impl App for App2048 {
             }
         });
 
+        if !ctx.wants_keyboard_input() {
+            let undo = egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::Z);
+            let redo = egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::Y);
+            if ctx.input_mut(|i| i.consume_shortcut(&undo)) {
+                self.undo();
+            }
+            if ctx.input_mut(|i| i.consume_shortcut(&redo)) {
+                self.redo();
+            }
+        }
+
+        if self.show_history {
+            egui::SidePanel::left("history_panel").show(ctx, |ui| {
+                self.history_panel(ui);
+            });
+        }
+        if self.show_predecessors {
+            egui::SidePanel::right("predecessors_panel").show(ctx, |ui| {
+                self.predecessors_panel(ui);
+            });
+        }
+
         egui::CentralPanel::default().show(ctx, |ui| {
-            ui.checkbox(&mut self.spawn_tile, "Enable tile spawn");
+            ui.horizontal(|ui| {
+                let gained = self.last_points.filter(|&p| p > 0).map(|p| format!(" (+{})", p)).unwrap_or_default();
+                ui.label(format!("Score: {}{}", self.score, gained))
+                    .on_hover_text("Boards loaded by ID start from the least score their tiles imply");
+                ui.label(format!("Best: {}", self.best_score));
+            });
+            ui.horizontal(|ui| {
+                if ui.checkbox(&mut self.spawn_tile, "Enable tile spawn").changed() && !self.spawn_tile {
+                    self.pending_spawn = None;
+                }
+                let mut extended = self.alphabet == Alphabet::Extended;
+                if ui.checkbox(&mut extended, "Extended tiles (up to 131072)").changed() {
+                    self.alphabet = if extended { Alphabet::Extended } else { Alphabet::Standard };
+                    self.update_editor_ids();
+                }
+                ui.checkbox(&mut self.show_canonical, "Show canonical ID");
+                ui.checkbox(&mut self.show_predecessors, "Show predecessors");
+                ui.checkbox(&mut self.show_history, "Show history");
+                self.best_move_button(ui);
+                let mut editing = self.editor.is_some();
+                if ui.checkbox(&mut editing, "Edit board")
+                    .on_hover_text("Click a cell to cycle its tile up, right-click to cycle it down")
+                    .changed()
+                {
+                    self.set_editing(editing);
+                }
+            });
+            if self.editor.is_some() {
+                ui.horizontal(|ui| match &self.editor_error {
+                    Some(msg) => {
+                        ui.colored_label(egui::Color32::RED, msg);
+                    }
+                    None => {
+                        ui.label(format!("Editing {}; uncheck Edit board to play it", self.address));
+                    }
+                });
+            }
+            if self.spawn_tile {
+                ui.horizontal(|ui| self.spawn_controls(ui));
+            }
+            ui.horizontal(|ui| self.ntuple_controls(ui));
+            ui.horizontal(|ui| self.recording_controls(ui));
+            if self.size.rows <= MAX_SOLVABLE_SIDE && self.size.cols <= MAX_SOLVABLE_SIDE {
+                ui.horizontal(|ui| self.retrograde_controls(ui));
+            }
             ui.with_layout(
                 egui::Layout::centered_and_justified(egui::Direction::TopDown),
                 |ui| {
                     let avail = ui.available_rect_before_wrap();
-                    let cell_size = avail.width().min(avail.height()) / 4.0;
-                    let grid_width = cell_size * 4.0;
-                    let grid_height = cell_size * 4.0;
+                    let (rows, cols) = (self.size.rows, self.size.cols);
+                    let cell_size = (avail.width() / cols as f32).min(avail.height() / rows as f32);
+                    let grid_width = cell_size * cols as f32;
+                    let grid_height = cell_size * rows as f32;
                     let grid_rect = egui::Rect::from_center_size(
                         avail.center(),
                         egui::vec2(grid_width, grid_height),
                     );
                     let mut y = grid_rect.top();
-                    for row in 0..4 {
+                    for row in 0..rows {
                         let mut x = grid_rect.left();
-                        for col in 0..4 {
+                        for col in 0..cols {
                             let rect = egui::Rect::from_min_size(
                                 egui::pos2(x, y), egui::vec2(cell_size, cell_size)
                             );
@@ -568,17 +1237,22 @@ impl App for App2048 {
                                 egui::StrokeKind::Middle,
                             );
                             if self.view_proto {
-                                if let Some(proto) = &self.current_proto {
-                                    if proto[row][col] == 'X' {
-                                        ui.painter().text(
-                                            rect.center(), egui::Align2::CENTER_CENTER,
-                                            "X",
-                                            egui::FontId::proportional(cell_size * 0.5),
-                                            egui::Color32::WHITE,
-                                        );
-                                    }
+                                if let Some(proto) = &self.current_proto
+                                    && proto.is_filled(row, col)
+                                {
+                                    ui.painter().text(
+                                        rect.center(), egui::Align2::CENTER_CENTER,
+                                        "X",
+                                        egui::FontId::proportional(cell_size * 0.5),
+                                        egui::Color32::WHITE,
+                                    );
                                 }
-                            } else if let Some(board) = &self.generated {
+                            } else if let Some(board) = self
+                                .editor
+                                .as_ref()
+                                .or(self.pending_spawn.as_ref().map(|pending| &pending.afterstate))
+                                .or(self.generated.as_ref())
+                            {
                                 let v = board[row][col];
                                 if v != 0 {
                                     let margin = cell_size * 0.03;
@@ -603,6 +1277,12 @@ impl App for App2048 {
                         }
                         y += cell_size;
                     }
+                    if self.editor.is_some() {
+                        self.board_editor(ui, grid_rect, cell_size);
+                    } else if !self.view_proto {
+                        self.manual_spawn_picker(ui, grid_rect, cell_size);
+                        self.status_overlay(ui, grid_rect);
+                    }
                 }
             );
         });
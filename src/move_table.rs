@@ -0,0 +1,174 @@
+use std::sync::LazyLock;
+
+use crate::bitboard::MAX_NIBBLE_EXPONENT;
+
+/// Result of sliding one packed row to the left: the new row and the points gained.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct RowMove {
+    pub row: u16,
+    pub score: u32,
+}
+
+// One entry per possible 16-bit row. None marks rows that would merge two 2^15
+// tiles, whose 2^16 result does not fit in a nibble.
+static LEFT_TABLE: LazyLock<Box<[Option<RowMove>]>> =
+    LazyLock::new(|| (0..=u16::MAX).map(slide_row_left).collect());
+
+// Every source row grouped by the row it slides left into: the exact inverse of
+// LEFT_TABLE, so ambiguous merges ([2,2,2] vs [4,2] both giving [4,2]) are all kept.
+static LEFT_INVERSE: LazyLock<Box<[Vec<u16>]>> = LazyLock::new(|| {
+    let mut inverse = vec![Vec::new(); 1 << 16];
+    for row in 0..=u16::MAX {
+        if let Some(moved) = LEFT_TABLE[row as usize] {
+            inverse[moved.row as usize].push(row);
+        }
+    }
+    inverse.into_boxed_slice()
+});
+
+// Same merge rules as `slide_and_merge_line`, on exponents instead of values.
+fn slide_row_left(row: u16) -> Option<RowMove> {
+    let line: Vec<u32> = (0..4)
+        .map(|i| ((row >> (4 * i)) & 0xf) as u32)
+        .filter(|&exp| exp != 0)
+        .collect();
+    let mut result = 0u16;
+    let mut score = 0;
+    let mut out = 0;
+    let mut i = 0;
+    while i < line.len() {
+        let exp = if i + 1 < line.len() && line[i] == line[i + 1] {
+            if line[i] == MAX_NIBBLE_EXPONENT {
+                return None;
+            }
+            score += 1 << (line[i] + 1);
+            i += 2;
+            line[i - 2] + 1
+        } else {
+            i += 1;
+            line[i - 1]
+        };
+        result |= (exp as u16) << (4 * out);
+        out += 1;
+    }
+    Some(RowMove { row: result, score })
+}
+
+pub fn reverse_row(row: u16) -> u16 {
+    (row >> 12) | ((row >> 4) & 0x00f0) | ((row << 4) & 0x0f00) | (row << 12)
+}
+
+/// Slides a row toward column 0.
+pub fn move_row_left(row: u16) -> Option<RowMove> {
+    LEFT_TABLE[row as usize]
+}
+
+/// Slides a row toward column 3.
+pub fn move_row_right(row: u16) -> Option<RowMove> {
+    let moved = LEFT_TABLE[reverse_row(row) as usize]?;
+    Some(RowMove { row: reverse_row(moved.row), score: moved.score })
+}
+
+/// Every row that slides left into `row`, including `row` itself when it is already packed.
+pub fn unmove_row_left(row: u16) -> &'static [u16] {
+    &LEFT_INVERSE[row as usize]
+}
+
+/// Every row that slides right into `row`.
+pub fn unmove_row_right(row: u16) -> Vec<u16> {
+    LEFT_INVERSE[reverse_row(row) as usize].iter().map(|&r| reverse_row(r)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bitboard::Bitboard;
+    use crate::board::{Direction, move_board_reference, slide_and_merge_line};
+
+    fn unpack(row: u16) -> Vec<u32> {
+        (0..4)
+            .map(|i| match (row >> (4 * i)) & 0xf {
+                0 => 0,
+                exp => 1 << exp,
+            })
+            .collect()
+    }
+
+    fn pack(values: &[u32]) -> Option<u16> {
+        let mut row = 0u16;
+        for (i, &v) in values.iter().enumerate() {
+            if v != 0 {
+                if v.ilog2() > MAX_NIBBLE_EXPONENT {
+                    return None;
+                }
+                row |= (v.ilog2() as u16) << (4 * i);
+            }
+        }
+        Some(row)
+    }
+
+    // Points gained by a reference slide: each merge doubles one tile and
+    // removes another, so the gain is the total of the merged results.
+    fn reference_score(line: &[u32]) -> u32 {
+        let tiles: Vec<u32> = line.iter().copied().filter(|&v| v != 0).collect();
+        let mut score = 0;
+        let mut i = 0;
+        while i < tiles.len() {
+            if i + 1 < tiles.len() && tiles[i] == tiles[i + 1] {
+                score += tiles[i] * 2;
+                i += 2;
+            } else {
+                i += 1;
+            }
+        }
+        score
+    }
+
+    #[test]
+    fn table_matches_reference_for_every_row() {
+        for row in 0..=u16::MAX {
+            let line = unpack(row);
+            let expected_left = slide_and_merge_line(line.clone());
+            let mut reversed = line.clone();
+            reversed.reverse();
+            let mut expected_right = slide_and_merge_line(reversed.clone());
+            expected_right.reverse();
+
+            match pack(&expected_left) {
+                Some(packed) => {
+                    let left = move_row_left(row).expect("row fits in nibbles");
+                    assert_eq!(left.row, packed, "left move of {:04x}", row);
+                    assert_eq!(left.score, reference_score(&line), "left score of {:04x}", row);
+                }
+                None => assert_eq!(move_row_left(row), None, "left overflow of {:04x}", row),
+            }
+            match pack(&expected_right) {
+                Some(packed) => {
+                    let right = move_row_right(row).expect("row fits in nibbles");
+                    assert_eq!(right.row, packed, "right move of {:04x}", row);
+                    assert_eq!(right.score, reference_score(&reversed), "right score of {:04x}", row);
+                }
+                None => assert_eq!(move_row_right(row), None, "right overflow of {:04x}", row),
+            }
+        }
+    }
+
+    #[test]
+    fn board_moves_match_reference_in_every_direction() {
+        // Fixed LCG so the sample of boards is the same on every run.
+        let mut state = 0x2048_u64;
+        for _ in 0..10_000 {
+            let mut packed = Bitboard::default();
+            for cell in 0..16 {
+                state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+                let exp = ((state >> 33) % 13) as u32;
+                packed.set(cell / 4, cell % 4, if exp > 11 { 0 } else { exp });
+            }
+            let board = packed.to_board();
+            for dir in Direction::ALL {
+                let (moved, _) = packed.move_dir(dir).expect("exponents stay below 15");
+                assert_eq!(moved.to_board(), move_board_reference(&board, dir), "{:?} on {}", dir, packed);
+            }
+        }
+    }
+}
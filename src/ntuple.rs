@@ -0,0 +1,291 @@
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Seek, Write};
+use std::path::Path;
+
+use crate::bitboard::Bitboard;
+use crate::board::{Board, Direction};
+use crate::expectimax::Heuristic;
+use crate::selfplay::Policy;
+use crate::spawn::SpawnModel;
+use crate::symmetry::Symmetry;
+
+/// Step size of one TD update, shared out across every weight it touches.
+pub const DEFAULT_LEARNING_RATE: f32 = 0.1;
+
+const MAGIC: &[u8; 4] = b"NTUP";
+const VERSION: u32 = 1;
+
+/// Cells (row * 4 + col) of the default tuples: an outer and an inner row, and
+/// corner, edge and centre 2x2 squares. Each is read under all eight symmetries.
+pub const DEFAULT_TUPLES: [[usize; 4]; 5] = [
+    [0, 1, 2, 3],
+    [4, 5, 6, 7],
+    [0, 1, 4, 5],
+    [1, 2, 5, 6],
+    [5, 6, 9, 10],
+];
+
+/// A move chosen by a value function: the direction, its afterstate and the
+/// points gained by its merges.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Afterstate {
+    pub direction: Direction,
+    pub board: Bitboard,
+    pub reward: u32,
+}
+
+/// An n-tuple network: a value function over 4x4 boards that sums one lookup
+/// table entry per tuple and symmetry, indexed by the exponents in the tuple's cells.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NTupleNetwork {
+    tuples: Vec<Vec<usize>>,
+    weights: Vec<Vec<f32>>,
+}
+
+impl Default for NTupleNetwork {
+    fn default() -> Self {
+        NTupleNetwork::new(DEFAULT_TUPLES.iter().map(|tuple| tuple.to_vec()).collect())
+    }
+}
+
+impl NTupleNetwork {
+    /// A network with all weights zero. Panics if a cell is off the board or a
+    /// tuple has more than 8 cells.
+    pub fn new(tuples: Vec<Vec<usize>>) -> Self {
+        for tuple in &tuples {
+            assert!(tuple.len() <= 8, "tuple of {} cells is too large", tuple.len());
+            assert!(tuple.iter().all(|&cell| cell < 16), "tuple cell off the board");
+        }
+        let weights = tuples.iter().map(|tuple| vec![0.0; 1 << (4 * tuple.len())]).collect();
+        NTupleNetwork { tuples, weights }
+    }
+
+    pub fn tuples(&self) -> &[Vec<usize>] {
+        &self.tuples
+    }
+
+    /// Estimated score still to be gained from `board`.
+    pub fn value(&self, board: Bitboard) -> f64 {
+        let mut value = 0.0;
+        for symmetry in Symmetry::ALL {
+            let view = symmetry.apply(board);
+            for (tuple, weights) in self.tuples.iter().zip(&self.weights) {
+                value += weights[index(view, tuple)] as f64;
+            }
+        }
+        value
+    }
+
+    // Moves every weight read by `value(board)` by `step`, split across them.
+    fn update(&mut self, board: Bitboard, step: f32) {
+        let share = step / (self.tuples.len() * Symmetry::ALL.len()) as f32;
+        for symmetry in Symmetry::ALL {
+            let view = symmetry.apply(board);
+            for (tuple, weights) in self.tuples.iter().zip(&mut self.weights) {
+                weights[index(view, tuple)] += share;
+            }
+        }
+    }
+
+    /// The legal move maximising reward plus afterstate value, or None if the game is over.
+    pub fn best_afterstate(&self, board: Bitboard) -> Option<Afterstate> {
+        Direction::ALL
+            .into_iter()
+            .filter_map(|direction| {
+                let (after, reward) = board.move_dir(direction)?;
+                (after != board).then_some(Afterstate { direction, board: after, reward })
+            })
+            .max_by(|a, b| {
+                let value = |m: &Afterstate| m.reward as f64 + self.value(m.board);
+                value(a).total_cmp(&value(b))
+            })
+    }
+
+    /// Plays one self-play game from a fresh two-tile board, learning from each
+    /// afterstate by TD(0), and returns the game's score. Tiles spawn as `spawns` draws them.
+    pub fn train_game(&mut self, learning_rate: f32, spawns: &mut SpawnModel) -> u64 {
+        let mut board = spawn(spawn(Bitboard(0), spawns), spawns);
+        let mut previous: Option<Bitboard> = None;
+        let mut score = 0u64;
+        while let Some(chosen) = self.best_afterstate(board) {
+            if let Some(previous) = previous {
+                let error = chosen.reward as f64 + self.value(chosen.board) - self.value(previous);
+                self.update(previous, learning_rate * error as f32);
+            }
+            previous = Some(chosen.board);
+            score += chosen.reward as u64;
+            board = spawn(chosen.board, spawns);
+        }
+        // Nothing more can be gained after the final afterstate.
+        if let Some(previous) = previous {
+            let error = -self.value(previous);
+            self.update(previous, learning_rate * error as f32);
+        }
+        score
+    }
+
+    /// Trains on `games` self-play games, returning the score of each.
+    pub fn train(&mut self, games: usize, learning_rate: f32, spawns: &mut SpawnModel) -> Vec<u64> {
+        (0..games).map(|_| self.train_game(learning_rate, spawns)).collect()
+    }
+
+    /// Writes the tuples and weights in a little-endian binary format.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let mut out = BufWriter::new(File::create(path)?);
+        out.write_all(MAGIC)?;
+        out.write_all(&VERSION.to_le_bytes())?;
+        out.write_all(&(self.tuples.len() as u32).to_le_bytes())?;
+        for (tuple, weights) in self.tuples.iter().zip(&self.weights) {
+            out.write_all(&(tuple.len() as u32).to_le_bytes())?;
+            for &cell in tuple {
+                out.write_all(&[cell as u8])?;
+            }
+            for weight in weights {
+                out.write_all(&weight.to_le_bytes())?;
+            }
+        }
+        out.flush()
+    }
+
+    /// Reads a network written by `save`.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let file = File::open(path)?;
+        let file_len = file.metadata()?.len();
+        let mut input = BufReader::new(file);
+        let mut magic = [0u8; 4];
+        input.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(invalid_data("not an n-tuple weights file".to_string()));
+        }
+        let version = read_u32(&mut input)?;
+        if version != VERSION {
+            return Err(invalid_data(format!("unsupported weights version {}", version)));
+        }
+        let count = read_u32(&mut input)?;
+        let mut tuples = Vec::new();
+        let mut weights = Vec::new();
+        for _ in 0..count {
+            let len = read_u32(&mut input)? as usize;
+            if len > 8 {
+                return Err(invalid_data(format!("tuple of {} cells is too large", len)));
+            }
+            let mut cells = vec![0u8; len];
+            input.read_exact(&mut cells)?;
+            if cells.iter().any(|&cell| cell >= 16) {
+                return Err(invalid_data("tuple cell off the board".to_string()));
+            }
+            // Check the table is really there before allocating up to 16 GiB for it.
+            if input.stream_position()? + (4u64 << (4 * len)) > file_len {
+                return Err(invalid_data(format!("weights file ends inside the table of a {}-cell tuple", len)));
+            }
+            let mut table = vec![0.0; 1 << (4 * len)];
+            for weight in &mut table {
+                let mut bytes = [0u8; 4];
+                input.read_exact(&mut bytes)?;
+                *weight = f32::from_le_bytes(bytes);
+            }
+            tuples.push(cells.into_iter().map(usize::from).collect());
+            weights.push(table);
+        }
+        Ok(NTupleNetwork { tuples, weights })
+    }
+}
+
+impl Heuristic for NTupleNetwork {
+    fn evaluate(&self, board: Bitboard) -> f64 {
+        self.value(board)
+    }
+}
+
+/// Plays the greedy afterstate choice on 4x4 boards and the first legal move otherwise.
+impl Policy for NTupleNetwork {
+    fn choose(&mut self, board: &Board, legal: &[Direction]) -> Direction {
+        Bitboard::from_board(board)
+            .and_then(|packed| self.best_afterstate(packed))
+            .map_or(legal[0], |best| best.direction)
+    }
+}
+
+// The exponents in `tuple`'s cells packed into a table index.
+fn index(board: Bitboard, tuple: &[usize]) -> usize {
+    tuple
+        .iter()
+        .enumerate()
+        .map(|(i, &cell)| (board.get(cell / 4, cell % 4) as usize) << (4 * i))
+        .sum()
+}
+
+fn spawn(board: Bitboard, spawns: &mut SpawnModel) -> Bitboard {
+    match spawns.spawn_packed(board) {
+        Some(spawn) => spawn.apply(board),
+        None => board,
+    }
+}
+
+fn read_u32(input: &mut impl Read) -> io::Result<u32> {
+    let mut bytes = [0u8; 4];
+    input.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::fs;
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        env::temp_dir().join(format!("ntuple-{}-{}.bin", std::process::id(), name))
+    }
+
+    fn board(tiles: &[(usize, usize, u32)]) -> Bitboard {
+        let mut board = Bitboard::default();
+        for &(row, col, exp) in tiles {
+            board.set(row, col, exp);
+        }
+        board
+    }
+
+    #[test]
+    fn saved_weights_load_back() {
+        let mut network = NTupleNetwork::new(vec![vec![0, 1, 2], vec![5, 9]]);
+        network.train(3, DEFAULT_LEARNING_RATE, &mut SpawnModel::classic(11));
+        let path = temp_path("round-trip");
+        network.save(&path).unwrap();
+        let loaded = NTupleNetwork::load(&path);
+        fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.unwrap(), network);
+    }
+
+    #[test]
+    fn truncated_tables_are_rejected_before_allocating() {
+        let mut bytes = MAGIC.to_vec();
+        for word in [VERSION, 1, 8] {
+            bytes.extend(word.to_le_bytes());
+        }
+        bytes.extend(0..8u8);
+        bytes.extend(1.0f32.to_le_bytes());
+        let path = temp_path("truncated");
+        fs::write(&path, bytes).unwrap();
+        let loaded = NTupleNetwork::load(&path);
+        fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn updates_move_the_value_towards_the_target() {
+        let mut network = NTupleNetwork::default();
+        let board = board(&[(0, 0, 3), (0, 1, 1), (2, 3, 5), (3, 3, 2)]);
+        for target in [40.0, -15.0, 0.0] {
+            let error = target - network.value(board);
+            network.update(board, DEFAULT_LEARNING_RATE * error as f32);
+            let remaining = target - network.value(board);
+            assert!(remaining.abs() < error.abs(), "{} -> {} for target {}", error, remaining, target);
+            assert!(remaining * error >= 0.0, "overshot target {}", target);
+        }
+    }
+}
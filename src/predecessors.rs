@@ -0,0 +1,145 @@
+use crate::bitboard::Bitboard;
+use crate::board::Direction;
+use crate::move_table::{unmove_row_left, unmove_row_right};
+use crate::spawn::Spawn;
+
+/// A board that reaches the target by moving in `direction` and then spawning `spawn`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Predecessor {
+    pub board: Bitboard,
+    pub direction: Direction,
+    pub spawn: Spawn,
+}
+
+/// Every board that slides into `afterstate` when moved in `dir`, including
+/// `afterstate` itself if the move leaves it unchanged.
+pub fn unmove(afterstate: Bitboard, dir: Direction) -> Vec<Bitboard> {
+    match dir {
+        Direction::Left => unmove_rows(afterstate, |row| unmove_row_left(row).to_vec()),
+        Direction::Right => unmove_rows(afterstate, unmove_row_right),
+        Direction::Up => unmove_rows(afterstate.transpose(), |row| unmove_row_left(row).to_vec())
+            .into_iter()
+            .map(Bitboard::transpose)
+            .collect(),
+        Direction::Down => unmove_rows(afterstate.transpose(), unmove_row_right)
+            .into_iter()
+            .map(Bitboard::transpose)
+            .collect(),
+    }
+}
+
+// Cartesian product of the source rows of each of the four rows.
+fn unmove_rows(afterstate: Bitboard, sources: impl Fn(u16) -> Vec<u16>) -> Vec<Bitboard> {
+    let mut boards = vec![afterstate];
+    for row in 0..4 {
+        let rows = sources(afterstate.row(row));
+        boards = boards
+            .iter()
+            .flat_map(|&board| {
+                rows.iter().map(move |&bits| {
+                    let mut source = board;
+                    source.set_row(row, bits);
+                    source
+                })
+            })
+            .collect();
+    }
+    boards
+}
+
+/// Every (board, direction, spawn) whose move changes the board and, with the
+/// spawned 2 or 4, yields `board`.
+pub fn predecessors(board: Bitboard) -> Vec<Predecessor> {
+    let mut found = Vec::new();
+    for row in 0..4 {
+        for col in 0..4 {
+            let exponent = board.get(row, col);
+            if exponent != 1 && exponent != 2 {
+                continue;
+            }
+            let spawn = Spawn { row, col, exponent };
+            let mut afterstate = board;
+            afterstate.set(row, col, 0);
+            for direction in Direction::ALL {
+                for source in unmove(afterstate, direction) {
+                    if source != afterstate {
+                        found.push(Predecessor { board: source, direction, spawn });
+                    }
+                }
+            }
+        }
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Fixed LCG so the sample of boards is the same on every run.
+    fn sample_boards(count: usize) -> Vec<Bitboard> {
+        let mut state = 0x8_u64;
+        (0..count)
+            .map(|_| {
+                let mut board = Bitboard::default();
+                for cell in 0..16 {
+                    state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+                    let exp = ((state >> 33) % 10) as u32;
+                    board.set(cell / 4, cell % 4, if exp > 4 { 0 } else { exp });
+                }
+                board
+            })
+            .collect()
+    }
+
+    fn spawns_on(afterstate: Bitboard) -> impl Iterator<Item = Spawn> {
+        (0..16)
+            .filter(move |&cell| afterstate.get(cell / 4, cell % 4) == 0)
+            .flat_map(|cell| [1, 2].map(|exponent| Spawn { row: cell / 4, col: cell % 4, exponent }))
+    }
+
+    #[test]
+    fn every_move_and_spawn_is_a_predecessor() {
+        for board in sample_boards(60) {
+            for direction in Direction::ALL {
+                let Some((afterstate, _)) = board.move_dir(direction).filter(|&(moved, _)| moved != board) else {
+                    continue;
+                };
+                for spawn in spawns_on(afterstate) {
+                    let target = spawn.apply(afterstate);
+                    let expected = Predecessor { board, direction, spawn };
+                    assert!(predecessors(target).contains(&expected), "{:?} missing from {}", expected, target);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn every_predecessor_replays_to_its_target() {
+        for target in sample_boards(300) {
+            for predecessor in predecessors(target) {
+                let (afterstate, _) = predecessor.board.move_dir(predecessor.direction).expect("small exponents");
+                assert_ne!(afterstate, predecessor.board, "{:?} does not move", predecessor);
+                assert_eq!(predecessor.spawn.apply(afterstate), target, "{:?}", predecessor);
+            }
+        }
+    }
+
+    #[test]
+    fn merged_and_unmerged_rows_share_an_afterstate() {
+        // [4, 2, 0, 0] is both its own left move and that of [2, 2, 2, 0].
+        let mut afterstate = Bitboard::default();
+        afterstate.set(0, 0, 2);
+        afterstate.set(0, 1, 1);
+        let mut merged = Bitboard::default();
+        for col in 0..3 {
+            merged.set(0, col, 1);
+        }
+        let sources = unmove(afterstate, Direction::Left);
+        assert!(sources.contains(&afterstate));
+        assert!(sources.contains(&merged));
+        for source in sources {
+            assert_eq!(source.move_dir(Direction::Left).map(|(moved, _)| moved), Some(afterstate), "{}", source);
+        }
+    }
+}
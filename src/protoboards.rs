@@ -1,20 +1,170 @@
+use std::fmt;
 use std::fs::File;
 use std::io::{BufWriter, Write};
 
-pub fn generate_protoboards() {
-    let file = File::create("protoboards.txt").expect("Unable to create file");
-    let mut writer = BufWriter::new(file);
+use crate::size::{BoardSize, MAX_SIDE};
 
-    let mut total_count = 0;
+/// Smallest tile count indexed by the library; the largest is the board's cell count.
+pub const MIN_T: u32 = 2;
+
+const MAX_CELLS: usize = MAX_SIDE * MAX_SIDE;
 
-    for t in 2..=16 {
-        let mut boards_for_t = Vec::new();
+// Pascal's triangle up to C(25, 25), built at compile time.
+const BINOMIAL: [[usize; MAX_CELLS + 1]; MAX_CELLS + 1] = {
+    let mut table = [[0usize; MAX_CELLS + 1]; MAX_CELLS + 1];
+    let mut n = 0;
+    while n <= MAX_CELLS {
+        table[n][0] = 1;
+        let mut k = 1;
+        while k <= n {
+            table[n][k] = table[n - 1][k - 1] + table[n - 1][k];
+            k += 1;
+        }
+        n += 1;
+    }
+    table
+};
+
+pub fn binomial(n: u32, k: u32) -> usize {
+    if k > n { 0 } else { BINOMIAL[n as usize][k as usize] }
+}
+
+/// Largest tile count on a board of this size.
+pub fn max_t(size: BoardSize) -> u32 {
+    size.cells() as u32
+}
 
-        for x in 0u16..=u16::MAX {
-            if x.count_ones() == t {
-                boards_for_t.push(x);
+/// Number of protoboards across all t in MIN_T..=max_t (2^cells minus the boards with t < 2).
+pub fn protoboard_count(size: BoardSize) -> usize {
+    (1 << size.cells()) - 1 - size.cells()
+}
+
+/// First and last global ID of the protoboards with t filled cells.
+pub fn id_range(size: BoardSize, t: u32) -> (GlobalId, GlobalId) {
+    let cells = max_t(size);
+    assert!((MIN_T..=cells).contains(&t), "t={} is outside {}..={}", t, MIN_T, cells);
+    let start = 1 + (MIN_T..t).map(|k| binomial(cells, k)).sum::<usize>();
+    (GlobalId(start), GlobalId(start + binomial(cells, t) - 1))
+}
+
+/// Index of a protoboard across all t values, starting at 1 for the first t=2 board.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct GlobalId(pub usize);
+
+impl GlobalId {
+    /// Tile count of the protoboards in this ID's range, or None if the ID is out of range.
+    pub fn t(self, size: BoardSize) -> Option<u32> {
+        let mut start = 1;
+        for t in MIN_T..=max_t(size) {
+            let count = binomial(max_t(size), t);
+            if (start..start + count).contains(&self.0) {
+                return Some(t);
             }
+            start += count;
         }
+        None
+    }
+}
+
+impl fmt::Display for GlobalId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Tile placement pattern of a board as an occupancy mask, with bit
+/// `row * cols + col` set for each occupied cell.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Protoboard {
+    size: BoardSize,
+    mask: u32,
+}
+
+impl Protoboard {
+    pub fn empty(size: BoardSize) -> Self {
+        Protoboard { size, mask: 0 }
+    }
+
+    pub fn from_mask(size: BoardSize, mask: u32) -> Self {
+        debug_assert!(mask >> size.cells() == 0, "mask has bits beyond {}", size);
+        Protoboard { size, mask }
+    }
+
+    pub fn size(self) -> BoardSize {
+        self.size
+    }
+
+    pub fn mask(self) -> u32 {
+        self.mask
+    }
+
+    pub fn t(self) -> u32 {
+        self.mask.count_ones()
+    }
+
+    pub fn is_filled(self, row: usize, col: usize) -> bool {
+        (self.mask >> (row * self.size.cols + col)) & 1 == 1
+    }
+
+    pub fn set_filled(&mut self, row: usize, col: usize) {
+        self.mask |= 1 << (row * self.size.cols + col);
+    }
+
+    /// Global ID of this protoboard: the cumulative count of all boards with fewer
+    /// tiles plus the combinadic rank of the mask among masks with the same t.
+    /// Masks of equal popcount in ascending numeric order are in colexicographic
+    /// order, which is exactly the order `generate_protoboards` writes them in.
+    pub fn global_id(self) -> Option<GlobalId> {
+        let t = self.t();
+        if t < MIN_T {
+            return None;
+        }
+        let (start, _) = id_range(self.size, t);
+        let mut rank = 0;
+        let mut i = 0;
+        for bit in 0..max_t(self.size) {
+            if (self.mask >> bit) & 1 == 1 {
+                i += 1;
+                rank += binomial(bit, i);
+            }
+        }
+        Some(GlobalId(start.0 + rank))
+    }
+
+    /// Inverse of `global_id`: unranks the combinadic greedily from the highest bit down.
+    pub fn from_global_id(size: BoardSize, gid: GlobalId) -> Option<Self> {
+        let t = gid.t(size)?;
+        let (start, _) = id_range(size, t);
+        let mut rank = gid.0 - start.0;
+        let mut mask = 0u32;
+        let mut bit = max_t(size);
+        for i in (1..=t).rev() {
+            bit -= 1;
+            while binomial(bit, i) > rank {
+                bit -= 1;
+            }
+            rank -= binomial(bit, i);
+            mask |= 1 << bit;
+        }
+        Some(Protoboard { size, mask })
+    }
+}
+
+/// Writes every protoboard of the given size to `protoboards.txt` (4x4) or
+/// `protoboards_RxC.txt`, in global ID order.
+pub fn generate_protoboards(size: BoardSize) {
+    let path = if size == BoardSize::CLASSIC {
+        "protoboards.txt".to_string()
+    } else {
+        format!("protoboards_{}.txt", size)
+    };
+    let file = File::create(&path).expect("Unable to create file");
+    let mut writer = BufWriter::new(file);
+
+    let mut total_count = 0;
+
+    for t in MIN_T..=max_t(size) {
+        let boards_for_t: Vec<u32> = masks_with_t(size, t).collect();
 
         writeln!(writer, "=============================").expect("Unable to write");
         writeln!(writer, "  Boards with t = {} filled tiles", t).expect("Unable to write");
@@ -28,9 +178,9 @@ pub fn generate_protoboards() {
             writeln!(writer, "Board #{} (t = {} filled tiles):", total_count, t)
                 .expect("Unable to write to file");
 
-            for row in 0..4 {
-                for col in 0..4 {
-                    let bit_index = row * 4 + col;
+            for row in 0..size.rows {
+                for col in 0..size.cols {
+                    let bit_index = row * size.cols + col;
                     let cell = (board >> bit_index) & 1;
                     let symbol = if cell == 1 { 'X' } else { '.' };
                     write!(writer, "{} ", symbol).expect("Unable to write to file");
@@ -42,5 +192,74 @@ pub fn generate_protoboards() {
         }
     }
 
-    println!("All {} protoboards written to protoboards.txt", total_count);
+    println!("All {} protoboards written to {}", total_count, path);
+}
+
+// Every mask of `size` with t cells filled, in ascending numeric order: the
+// order `generate_protoboards` writes them in.
+fn masks_with_t(size: BoardSize, t: u32) -> impl Iterator<Item = u32> {
+    let limit = 1u64 << size.cells();
+    // Gosper's hack steps to the next larger integer with the same popcount.
+    std::iter::successors(Some((1u64 << t) - 1), |&x| {
+        let lowest = x & x.wrapping_neg();
+        let ripple = x + lowest;
+        Some((((ripple ^ x) >> 2) / lowest) | ripple)
+    })
+    .take_while(move |&x| x < limit)
+    .map(|x| x as u32)
+}
+
+pub fn count_filled(board: &Protoboard) -> usize {
+    board.t() as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Boards up to this many cells are checked mask by mask. Larger ones have up
+    // to 33.5 million protoboards, too many to rank in a debug build, so only
+    // `LARGE_BOARD_SAMPLE` masks at each end of every t are checked there.
+    const EXHAUSTIVE_CELLS: usize = 16;
+    const LARGE_BOARD_SAMPLE: usize = 5_000;
+
+    fn assert_ranks(size: BoardSize, masks: impl Iterator<Item = (usize, u32)>) {
+        for (gid, mask) in masks {
+            let proto = Protoboard::from_mask(size, mask);
+            assert_eq!(proto.global_id(), Some(GlobalId(gid)), "mask {:#b} on {}", mask, size);
+            assert_eq!(Protoboard::from_global_id(size, GlobalId(gid)), Some(proto));
+        }
+    }
+
+    #[test]
+    fn global_ids_follow_emission_order() {
+        for size in BoardSize::all() {
+            let cells = max_t(size);
+            let mut next = 1;
+            for t in MIN_T..=cells {
+                let (start, end) = id_range(size, t);
+                assert_eq!(start, GlobalId(next), "first ID of t={} on {}", t, size);
+                assert_eq!(end.0 - start.0 + 1, binomial(cells, t));
+                if size.cells() <= EXHAUSTIVE_CELLS {
+                    let masks: Vec<u32> = masks_with_t(size, t).collect();
+                    assert_eq!(masks.len(), binomial(cells, t), "t={} on {}", t, size);
+                    assert!(masks.iter().all(|mask| mask.count_ones() == t));
+                    assert_ranks(size, (start.0..).zip(masks));
+                } else {
+                    assert_ranks(size, (start.0..).zip(masks_with_t(size, t).take(LARGE_BOARD_SAMPLE)));
+                    // Complements of the masks with the other cells filled come
+                    // in descending order, giving the end of the range.
+                    if t < cells {
+                        let full = (1u32 << cells) - 1;
+                        let last = masks_with_t(size, cells - t).take(LARGE_BOARD_SAMPLE).map(|mask| !mask & full);
+                        assert_ranks(size, (0..).map(|i| end.0 - i).zip(last));
+                    }
+                }
+                next = end.0 + 1;
+            }
+            assert_eq!(next - 1, protoboard_count(size));
+            assert_eq!(Protoboard::from_global_id(size, GlobalId(next)), None);
+            assert_eq!(Protoboard::from_global_id(size, GlobalId(0)), None);
+        }
+    }
 }
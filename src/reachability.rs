@@ -0,0 +1,202 @@
+use std::collections::HashSet;
+use std::fmt;
+
+use crate::bitboard::Bitboard;
+use crate::board::Direction;
+use crate::predecessors::predecessors;
+use crate::spawn::Spawn;
+
+/// Node budget used by `classify`.
+pub const DEFAULT_BUDGET: usize = 100_000;
+
+/// One turn of play: a move followed by a spawned tile.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Step {
+    pub direction: Direction,
+    pub spawn: Spawn,
+}
+
+/// A game that reaches the target: a legal two-tile start followed by `steps`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Proof {
+    pub start: Bitboard,
+    pub steps: Vec<Step>,
+}
+
+impl Proof {
+    /// Replays the proof, returning the board after every step (start included).
+    pub fn boards(&self) -> Vec<Bitboard> {
+        let mut boards = vec![self.start];
+        let mut board = self.start;
+        for step in &self.steps {
+            let (moved, _) = board.move_dir(step.direction).expect("proof moves stay in range");
+            board = step.spawn.apply(moved);
+            boards.push(board);
+        }
+        boards
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Unreachable {
+    TooFewTiles,
+    NoSpawnedTile,
+    NoPredecessors,
+    NoPathToStart { explored: usize },
+}
+
+impl fmt::Display for Unreachable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Unreachable::TooFewTiles => write!(f, "every game position has at least two tiles"),
+            Unreachable::NoSpawnedTile => write!(f, "no 2 or 4 tile, so no tile can have just spawned"),
+            Unreachable::NoPredecessors => write!(f, "no move and spawn produces this board"),
+            Unreachable::NoPathToStart { explored } => {
+                write!(f, "none of the {} earlier positions leads back to a two-tile start", explored)
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Reachability {
+    Reachable(Proof),
+    Unreachable(Unreachable),
+    /// The search budget ran out before a verdict.
+    Unknown { explored: usize },
+}
+
+fn has_spawnable_tile(board: Bitboard) -> bool {
+    (0..4).any(|row| (0..4).any(|col| matches!(board.get(row, col), 1 | 2)))
+}
+
+fn is_start(board: Bitboard) -> bool {
+    board.protoboard().t() == 2 && (0..16).all(|i| board.get(i / 4, i % 4) <= 2)
+}
+
+/// Whether `board` can occur in a game started from two 2/4 tiles, searched
+/// backwards through predecessors with at most `DEFAULT_BUDGET` visited boards.
+pub fn classify(board: Bitboard) -> Reachability {
+    classify_with_budget(board, DEFAULT_BUDGET)
+}
+
+pub fn classify_with_budget(board: Bitboard, budget: usize) -> Reachability {
+    if board.protoboard().t() < 2 {
+        return Reachability::Unreachable(Unreachable::TooFewTiles);
+    }
+    if !has_spawnable_tile(board) {
+        return Reachability::Unreachable(Unreachable::NoSpawnedTile);
+    }
+    if is_start(board) {
+        return Reachability::Reachable(Proof { start: board, steps: Vec::new() });
+    }
+    if predecessors(board).is_empty() {
+        return Reachability::Unreachable(Unreachable::NoPredecessors);
+    }
+    let mut search = Search { dead_ends: HashSet::new(), budget, exhausted: false };
+    match search.path_to_start(board) {
+        Some((start, steps)) => Reachability::Reachable(Proof { start, steps }),
+        None if search.exhausted => Reachability::Unknown { explored: search.dead_ends.len() },
+        None => Reachability::Unreachable(Unreachable::NoPathToStart { explored: search.dead_ends.len() }),
+    }
+}
+
+struct Search {
+    dead_ends: HashSet<Bitboard>,
+    budget: usize,
+    exhausted: bool,
+}
+
+impl Search {
+    // Depth-first search backwards; every predecessor has a smaller tile sum, so
+    // this terminates. Each level appends the step into its own board, so the
+    // steps come back in play order.
+    fn path_to_start(&mut self, board: Bitboard) -> Option<(Bitboard, Vec<Step>)> {
+        if is_start(board) {
+            return Some((board, Vec::new()));
+        }
+        if self.dead_ends.contains(&board) {
+            return None;
+        }
+        if self.dead_ends.len() >= self.budget {
+            self.exhausted = true;
+            return None;
+        }
+        let mut candidates: Vec<_> = predecessors(board)
+            .into_iter()
+            .filter(|p| p.board.protoboard().t() >= 2 && has_spawnable_tile(p.board))
+            .collect();
+        // Fewer tiles first: those boards are closer to a two-tile start.
+        candidates.sort_by_key(|p| (p.board.protoboard().t(), p.board));
+        candidates.dedup_by_key(|p| p.board);
+        for p in candidates {
+            if let Some((start, mut steps)) = self.path_to_start(p.board) {
+                steps.push(Step { direction: p.direction, spawn: p.spawn });
+                return Some((start, steps));
+            }
+            if self.exhausted {
+                return None;
+            }
+        }
+        self.dead_ends.insert(board);
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spawn::SpawnModel;
+
+    fn board(tiles: &[(usize, usize, u32)]) -> Bitboard {
+        let mut board = Bitboard::default();
+        for &(row, col, exp) in tiles {
+            board.set(row, col, exp);
+        }
+        board
+    }
+
+    #[test]
+    fn two_tile_start_needs_no_steps() {
+        let start = board(&[(0, 0, 1), (2, 3, 2)]);
+        assert_eq!(classify(start), Reachability::Reachable(Proof { start, steps: Vec::new() }));
+    }
+
+    #[test]
+    fn board_without_a_two_or_four_is_unreachable() {
+        let board = board(&[(0, 0, 3), (1, 1, 4)]);
+        assert_eq!(classify(board), Reachability::Unreachable(Unreachable::NoSpawnedTile));
+    }
+
+    #[test]
+    fn two_1024s_cannot_both_be_built() {
+        let board = board(&[(0, 0, 10), (0, 1, 10), (3, 3, 1)]);
+        assert!(matches!(classify(board), Reachability::Unreachable(Unreachable::NoPathToStart { .. })));
+    }
+
+    #[test]
+    fn proof_replays_a_played_game() {
+        let mut spawns = SpawnModel::classic(7);
+        let mut target = Bitboard::default();
+        for _ in 0..2 {
+            target = spawns.spawn_packed(target).expect("empty board").apply(target);
+        }
+        // Cycle through the directions, skipping any that leave the board unchanged.
+        for turn in 0..12 {
+            let Some((moved, _)) = (0..4)
+                .map(|i| Direction::ALL[(turn + i) % 4])
+                .filter_map(|direction| target.move_dir(direction))
+                .find(|&(moved, _)| moved != target)
+            else {
+                break;
+            };
+            target = spawns.spawn_packed(moved).expect("a move leaves an empty cell").apply(moved);
+        }
+        let Reachability::Reachable(proof) = classify(target) else {
+            panic!("{} was played from a new game", target);
+        };
+        let boards = proof.boards();
+        assert!(is_start(boards[0]), "{}", boards[0]);
+        assert_eq!(boards.last(), Some(&target));
+    }
+}
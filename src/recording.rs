@@ -0,0 +1,236 @@
+//! Plain-text game recordings.
+//!
+//! A recording is a start board, the turns played from it and, optionally, the
+//! board it ends on:
+//!
+//! ```text
+//! library-of-2048 recording v1
+//! start 2:119:AA
+//! L 2@0,3
+//! U 4@3,3
+//! R
+//! final 3:581:B12
+//! ```
+//!
+//! The first line is the header. `start` and `final` hold board addresses. Each
+//! turn is a direction letter (`U`, `D`, `L`, `R`), followed by the tile that
+//! spawned as `value@row,col` unless the move spawned nothing. Blank lines and
+//! lines starting with `#` are ignored. When `final` is present, loading checks
+//! that replaying the turns reaches it. A last board outside the library, e.g.
+//! one holding two 2048s, has no address, so saving writes a comment in its place.
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::address::BoardAddress;
+use crate::board::{Board, Direction, move_board_scored};
+use crate::local_id::MAX_EXPONENT;
+use crate::score::min_score;
+use crate::spawn::Spawn;
+
+const HEADER: &str = "library-of-2048 recording v1";
+
+/// One move and the tile that spawned after it, if any.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Turn {
+    pub direction: Direction,
+    pub spawn: Option<Spawn>,
+}
+
+/// A board reached while replaying, with the score earned so far. The start
+/// board scores the least its tiles could have earned.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReplayStep {
+    pub board: Board,
+    pub score: u64,
+}
+
+/// A played session: where it started and every turn since.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Recording {
+    pub start: BoardAddress,
+    pub turns: Vec<Turn>,
+}
+
+impl Recording {
+    pub fn new(start: BoardAddress) -> Self {
+        Recording { start, turns: Vec::new() }
+    }
+
+    pub fn push(&mut self, direction: Direction, spawn: Option<Spawn>) {
+        self.turns.push(Turn { direction, spawn });
+    }
+
+    /// The start board followed by the board after each turn.
+    pub fn replay(&self) -> Result<Vec<ReplayStep>, String> {
+        let mut board = self.start.to_board();
+        let mut score = min_score(&board);
+        let mut steps = vec![ReplayStep { board: board.clone(), score }];
+        for (i, turn) in self.turns.iter().enumerate() {
+            let (moved, points) = move_board_scored(&board, turn.direction);
+            board = moved;
+            score += points as u64;
+            if let Some(spawn) = turn.spawn {
+                let size = board.size();
+                if spawn.row >= size.rows || spawn.col >= size.cols || board[spawn.row][spawn.col] != 0 {
+                    return Err(format!(
+                        "Turn {}: cannot spawn at ({}, {}), the cell is not empty",
+                        i + 1, spawn.row, spawn.col
+                    ));
+                }
+                board = spawn.apply_to_board(&board);
+            }
+            steps.push(ReplayStep { board: board.clone(), score });
+        }
+        Ok(steps)
+    }
+
+    /// Address of the last board, or None if the turns cannot be replayed or
+    /// end on a board outside the library.
+    pub fn final_address(&self) -> Option<BoardAddress> {
+        let steps = self.replay().ok()?;
+        BoardAddress::from_board(&steps.last()?.board)
+    }
+}
+
+impl fmt::Display for Recording {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", HEADER)?;
+        writeln!(f, "start {}", self.start)?;
+        for turn in &self.turns {
+            write!(f, "{}", direction_letter(turn.direction))?;
+            if let Some(spawn) = turn.spawn {
+                write!(f, " {}@{},{}", spawn.value(), spawn.row, spawn.col)?;
+            }
+            writeln!(f)?;
+        }
+        if let Ok(steps) = self.replay()
+            && let Some(last) = steps.last()
+        {
+            match BoardAddress::try_from_board(&last.board) {
+                Ok(address) => writeln!(f, "final {}", address)?,
+                Err(e) => writeln!(f, "# no final board: {}", e)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Recording {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut lines = s
+            .lines()
+            .enumerate()
+            .map(|(i, line)| (i + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'));
+
+        match lines.next() {
+            Some((_, HEADER)) => {}
+            _ => return Err(format!("Missing header '{}'", HEADER)),
+        }
+        let start = match lines.next() {
+            Some((n, line)) => match line.strip_prefix("start ") {
+                Some(address) => address.parse::<BoardAddress>().map_err(|e| format!("Line {}: {}", n, e))?,
+                None => return Err(format!("Line {}: expected 'start <address>'", n)),
+            },
+            None => return Err("Missing start board".to_string()),
+        };
+
+        let mut recording = Recording::new(start);
+        let mut expected_final = None;
+        for (n, line) in lines {
+            if expected_final.is_some() {
+                return Err(format!("Line {}: nothing may follow the final board", n));
+            }
+            if let Some(address) = line.strip_prefix("final ") {
+                expected_final = Some(address.parse::<BoardAddress>().map_err(|e| format!("Line {}: {}", n, e))?);
+                continue;
+            }
+            let turn = parse_turn(line).map_err(|e| format!("Line {}: {}", n, e))?;
+            recording.turns.push(turn);
+        }
+
+        let steps = recording.replay()?;
+        if let Some(expected) = expected_final {
+            let reached = steps.last().and_then(|step| BoardAddress::from_board(&step.board));
+            if reached.as_ref() != Some(&expected) {
+                return Err(format!("Replaying the turns does not reach the final board {}", expected));
+            }
+        }
+        Ok(recording)
+    }
+}
+
+fn direction_letter(direction: Direction) -> char {
+    match direction {
+        Direction::Up => 'U',
+        Direction::Down => 'D',
+        Direction::Left => 'L',
+        Direction::Right => 'R',
+    }
+}
+
+fn parse_turn(line: &str) -> Result<Turn, String> {
+    let mut parts = line.split_whitespace();
+    let direction = match parts.next() {
+        Some("U") => Direction::Up,
+        Some("D") => Direction::Down,
+        Some("L") => Direction::Left,
+        Some("R") => Direction::Right,
+        other => return Err(format!("unknown direction '{}'", other.unwrap_or(""))),
+    };
+    let spawn = parts.next().map(parse_spawn).transpose()?;
+    if parts.next().is_some() {
+        return Err("unexpected text after the spawn".to_string());
+    }
+    Ok(Turn { direction, spawn })
+}
+
+// `value@row,col`, e.g. `2@0,3`.
+fn parse_spawn(s: &str) -> Result<Spawn, String> {
+    let malformed = || format!("malformed spawn '{}', expected value@row,col", s);
+    let (value, cell) = s.split_once('@').ok_or_else(malformed)?;
+    let (row, col) = cell.split_once(',').ok_or_else(malformed)?;
+    let exponent = match value.parse::<u32>() {
+        Ok(value) if value >= 2 && value.is_power_of_two() && value.trailing_zeros() <= MAX_EXPONENT => {
+            value.trailing_zeros()
+        }
+        _ => return Err(format!("spawned tile must be a power of two up to {}, not '{}'", 1u32 << MAX_EXPONENT, value)),
+    };
+    let row = row.parse().map_err(|_| malformed())?;
+    let col = col.parse().map_err(|_| malformed())?;
+    Ok(Spawn { row, col, exponent })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn start(rows: Vec<Vec<u32>>) -> Recording {
+        Recording::new(BoardAddress::from_board(&Board::from_rows(rows)).expect("start board is in the library"))
+    }
+
+    #[test]
+    fn saved_recordings_load_back() {
+        let mut recording = start(vec![vec![2, 0, 0, 0], vec![0, 0, 0, 0], vec![0, 0, 4, 0], vec![0, 0, 0, 2]]);
+        recording.push(Direction::Left, Some(Spawn { row: 0, col: 3, exponent: 1 }));
+        recording.push(Direction::Up, Some(Spawn { row: 3, col: 3, exponent: 2 }));
+        recording.push(Direction::Right, None);
+        let text = recording.to_string();
+        assert!(text.contains(&format!("final {}", recording.final_address().unwrap())));
+        assert_eq!(text.parse::<Recording>(), Ok(recording));
+    }
+
+    #[test]
+    fn final_board_outside_the_library_is_left_out() {
+        let mut recording = start(vec![vec![1024, 1024, 2048, 0], vec![0; 4], vec![0; 4], vec![0, 0, 0, 2]]);
+        recording.push(Direction::Left, Some(Spawn { row: 0, col: 3, exponent: 1 }));
+        assert_eq!(recording.final_address(), None);
+        let text = recording.to_string();
+        assert!(!text.lines().any(|line| line.starts_with("final ")));
+        assert!(text.contains("# no final board"));
+        assert_eq!(text.parse::<Recording>(), Ok(recording));
+    }
+}
@@ -0,0 +1,295 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::hash::{BuildHasherDefault, Hasher};
+
+use crate::bitboard::Bitboard;
+use crate::board::{Board, Direction};
+use crate::library_index::LibraryIndex;
+use crate::local_id::{Alphabet, MAX_EXPONENT};
+use crate::size::BoardSize;
+use crate::spawn::TWO_PROBABILITY;
+
+/// Longest side of a board `RetrogradeTable::solve` accepts.
+pub const MAX_SOLVABLE_SIDE: usize = 3;
+
+/// Exact win probabilities under optimal play and the classic spawn rule for
+/// every non-winning state reachable from a new game, on a board of at most 3x3
+/// cells. Values are keyed by the board's library index, stored sorted for
+/// binary search.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RetrogradeTable {
+    size: BoardSize,
+    goal: u32,
+    keys: Vec<u64>,
+    values: Vec<f32>,
+}
+
+impl RetrogradeTable {
+    /// Solves `size` for reaching a tile of exponent `goal` (e.g. 5 for 32),
+    /// starting from every two-tile opening.
+    pub fn solve(size: BoardSize, goal: u32) -> Result<Self, String> {
+        if size.rows > MAX_SOLVABLE_SIDE || size.cols > MAX_SOLVABLE_SIDE {
+            return Err(format!("Board size {} is too large to solve exactly", size));
+        }
+        if !(2..=MAX_EXPONENT).contains(&goal) {
+            return Err(format!("Goal tile must be between 4 and {}", 1u32 << MAX_EXPONENT));
+        }
+        let cells = cells(size);
+        let layers = reachable_layers(size, goal, &cells);
+
+        // Every spawn adds to the tile sum, so each layer only depends on later
+        // ones and the last layer has nothing left to solve.
+        let mut solved = BoardMap::default();
+        for layer in layers.iter().rev() {
+            for &board in layer {
+                let value = if board.max_exponent() >= goal { 1.0 } else { best_value(board, size, &cells, &solved) };
+                solved.insert(board, value);
+            }
+        }
+
+        let mut entries: Vec<(u64, f32)> = solved
+            .into_iter()
+            .filter(|&(board, _)| board.max_exponent() < goal)
+            .map(|(board, value)| {
+                let index = LibraryIndex::from_board(&unpack(board, size, &cells), Alphabet::Standard)
+                    .expect("tiles below the goal fit the standard alphabet");
+                (index.0 as u64, value as f32)
+            })
+            .collect();
+        entries.sort_unstable_by_key(|&(key, _)| key);
+        let (keys, values) = entries.into_iter().unzip();
+        Ok(RetrogradeTable { size, goal, keys, values })
+    }
+
+    pub fn size(&self) -> BoardSize {
+        self.size
+    }
+
+    /// Exponent of the goal tile.
+    pub fn goal(&self) -> u32 {
+        self.goal
+    }
+
+    /// Number of stored states.
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Stored win probability of the state with library index `index`.
+    pub fn get(&self, index: LibraryIndex) -> Option<f32> {
+        let key = u64::try_from(index.0).ok()?;
+        self.keys.binary_search(&key).ok().map(|i| self.values[i])
+    }
+
+    /// Optimal win probability of `board`: 1 if it already holds the goal tile,
+    /// None if it is the wrong size or unreachable from a new game.
+    pub fn win_probability(&self, board: &Board) -> Option<f64> {
+        if board.size() != self.size {
+            return None;
+        }
+        if board.rows().iter().flatten().any(|&v| v >= 1 << self.goal) {
+            return Some(1.0);
+        }
+        let index = LibraryIndex::from_board(board, Alphabet::Standard)?;
+        self.get(index).map(f64::from)
+    }
+}
+
+// Every state reachable from a two-tile opening without passing through a win,
+// grouped by tile sum in increasing order.
+fn reachable_layers(size: BoardSize, goal: u32, cells: &[(usize, usize)]) -> Vec<Vec<Bitboard>> {
+    let mut pending: BTreeMap<u32, BoardSet> = BTreeMap::new();
+    for (i, &(r1, c1)) in cells.iter().enumerate() {
+        for &(r2, c2) in &cells[i + 1..] {
+            for (e1, e2) in [(1, 1), (1, 2), (2, 1), (2, 2)] {
+                let mut board = Bitboard::default();
+                board.set(r1, c1, e1);
+                board.set(r2, c2, e2);
+                pending.entry((1 << e1) + (1 << e2)).or_default().insert(board);
+            }
+        }
+    }
+
+    let mut layers = Vec::new();
+    while let Some((sum, boards)) = pending.pop_first() {
+        // Spawning a 2 or a 4 feeds the layers two and four above this one.
+        let mut next = [sum + 2, sum + 4].map(|next_sum| pending.remove(&next_sum).unwrap_or_default());
+        for &board in &boards {
+            if board.max_exponent() >= goal {
+                continue;
+            }
+            for afterstate in afterstates(board, size).into_iter().flatten() {
+                for &(row, col) in cells {
+                    if afterstate.get(row, col) != 0 {
+                        continue;
+                    }
+                    for (exponent, next) in [1, 2].into_iter().zip(&mut next) {
+                        let mut spawned = afterstate;
+                        spawned.set(row, col, exponent);
+                        next.insert(spawned);
+                    }
+                }
+            }
+        }
+        for (next_sum, next) in [sum + 2, sum + 4].into_iter().zip(next) {
+            if !next.is_empty() {
+                pending.insert(next_sum, next);
+            }
+        }
+        layers.push(boards.into_iter().collect());
+    }
+    layers
+}
+
+type BoardSet = HashSet<Bitboard, BuildHasherDefault<BoardHasher>>;
+type BoardMap<V> = HashMap<Bitboard, V, BuildHasherDefault<BoardHasher>>;
+
+// Packed boards are already well spread, so one multiply-xorshift mixes them
+// far faster than the default SipHash.
+#[derive(Default)]
+struct BoardHasher(u64);
+
+impl Hasher for BoardHasher {
+    fn finish(&self) -> u64 {
+        let mixed = self.0.wrapping_mul(0x9E37_79B9_7F4A_7C15);
+        mixed ^ (mixed >> 32)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = (self.0 << 8) | byte as u64;
+        }
+    }
+
+    fn write_u64(&mut self, value: u64) {
+        self.0 = value;
+    }
+}
+
+// Expected value of the best move, each afterstate averaged over its spawns.
+fn best_value(board: Bitboard, size: BoardSize, cells: &[(usize, usize)], solved: &BoardMap<f64>) -> f64 {
+    let mut best = 0.0;
+    for afterstate in afterstates(board, size).into_iter().flatten() {
+        let mut empty = 0;
+        let mut expected = 0.0;
+        for &(row, col) in cells {
+            if afterstate.get(row, col) != 0 {
+                continue;
+            }
+            empty += 1;
+            for (exponent, weight) in [(1, TWO_PROBABILITY), (2, 1.0 - TWO_PROBABILITY)] {
+                let mut spawned = afterstate;
+                spawned.set(row, col, exponent);
+                expected += weight * solved[&spawned];
+            }
+        }
+        best = f64::max(best, expected / empty as f64);
+    }
+    best
+}
+
+// The boards `board` can move to, one per direction that changes it.
+fn afterstates(board: Bitboard, size: BoardSize) -> [Option<Bitboard>; 4] {
+    Direction::ALL.map(|dir| Some(slide(board, size, dir)).filter(|&after| after != board))
+}
+
+// A small board sits in the top-left corner of a Bitboard, so left and up moves
+// come straight from the row table. For right and down the board is first
+// shifted against the far edge, which is empty on boards under four wide.
+fn slide(board: Bitboard, size: BoardSize, dir: Direction) -> Bitboard {
+    match dir {
+        Direction::Left => moved(board, Direction::Left),
+        Direction::Right => slide_right(board, size.cols),
+        Direction::Up => moved(board.transpose(), Direction::Left).transpose(),
+        Direction::Down => slide_right(board.transpose(), size.rows).transpose(),
+    }
+}
+
+fn slide_right(board: Bitboard, width: usize) -> Bitboard {
+    let shift = 4 * (4 - width);
+    Bitboard(moved(Bitboard(board.0 << shift), Direction::Right).0 >> shift)
+}
+
+fn moved(board: Bitboard, dir: Direction) -> Bitboard {
+    board.move_dir(dir).expect("small-board tiles stay below 2^15").0
+}
+
+fn cells(size: BoardSize) -> Vec<(usize, usize)> {
+    (0..size.rows).flat_map(|row| (0..size.cols).map(move |col| (row, col))).collect()
+}
+
+fn unpack(board: Bitboard, size: BoardSize, cells: &[(usize, usize)]) -> Board {
+    let mut unpacked = Board::empty(size);
+    for &(row, col) in cells {
+        let exp = board.get(row, col);
+        if exp != 0 {
+            unpacked[row][col] = 1 << exp;
+        }
+    }
+    unpacked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::successors::successors;
+
+    // Optimal win probability straight from the definition, memoised on every
+    // board it visits.
+    fn brute_force(board: &Board, goal: u32, memo: &mut HashMap<Board, f64>) -> f64 {
+        if board.rows().iter().flatten().any(|&v| v >= 1 << goal) {
+            return 1.0;
+        }
+        if let Some(&value) = memo.get(board) {
+            return value;
+        }
+        let mut best = 0.0;
+        for successor in successors(board) {
+            let expected = successor
+                .outcomes
+                .iter()
+                .map(|outcome| outcome.probability * brute_force(&outcome.board, goal, memo))
+                .sum();
+            best = f64::max(best, expected);
+        }
+        memo.insert(board.clone(), best);
+        best
+    }
+
+    #[test]
+    fn solve_matches_brute_force_on_2x2() {
+        let size = BoardSize::new(2, 2).unwrap();
+        let cells = cells(size);
+        for goal in [4, 5] {
+            let table = RetrogradeTable::solve(size, goal).unwrap();
+            let mut memo = HashMap::new();
+            for (i, &(r1, c1)) in cells.iter().enumerate() {
+                for &(r2, c2) in &cells[i + 1..] {
+                    for (v1, v2) in [(2, 2), (2, 4), (4, 2), (4, 4)] {
+                        let mut opening = Board::empty(size);
+                        opening[r1][c1] = v1;
+                        opening[r2][c2] = v2;
+                        brute_force(&opening, goal, &mut memo);
+                    }
+                }
+            }
+            assert_eq!(table.len(), memo.len(), "goal {}", 1 << goal);
+            for (board, expected) in &memo {
+                let solved = table.win_probability(board).expect("reachable board is stored");
+                assert!((solved - expected).abs() < 1e-6, "{:?}: {} vs {}", board, solved, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn win_probability_of_won_and_foreign_boards() {
+        let table = RetrogradeTable::solve(BoardSize::new(2, 2).unwrap(), 4).unwrap();
+        let won = Board::from_rows(vec![vec![16, 2], vec![0, 0]]);
+        assert_eq!(table.win_probability(&won), Some(1.0));
+        let wrong_size = Board::from_rows(vec![vec![2, 2, 0], vec![0, 0, 0]]);
+        assert_eq!(table.win_probability(&wrong_size), None);
+    }
+}
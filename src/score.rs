@@ -0,0 +1,28 @@
+use crate::board::Board;
+
+/// The least score a game could have earned on its way to `board`.
+///
+/// A tile of 2^k built from spawned 2s earns (k - 1) * 2^k points in merges;
+/// every 4 that spawned instead saves the 4 points of its own merge. The minimum
+/// assumes every tile was built from spawned 4s, giving (k - 2) * 2^k per tile.
+pub fn min_score(board: &Board) -> u64 {
+    board
+        .rows()
+        .iter()
+        .flatten()
+        .filter(|&&v| v >= 8)
+        .map(|&v| (v.ilog2() as u64 - 2) * v as u64)
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tiles_score_as_if_built_from_fours() {
+        assert_eq!(min_score(&Board::from_rows(vec![vec![2, 4], vec![0, 0]])), 0);
+        assert_eq!(min_score(&Board::from_rows(vec![vec![8, 16], vec![0, 2]])), 8 + 32);
+        assert_eq!(min_score(&Board::from_rows(vec![vec![2048, 0], vec![0, 0]])), 9 * 2048);
+    }
+}
@@ -0,0 +1,113 @@
+use rand::Rng;
+use rand::seq::IndexedRandom;
+
+use crate::address::BoardAddress;
+use crate::bitboard::Bitboard;
+use crate::board::{Board, Direction, move_board_scored};
+use crate::expectimax::{Expectimax, Heuristic};
+use crate::spawn::SpawnPolicy;
+use crate::status::legal_moves;
+
+/// Chooses the next move of a game from the directions that change the board.
+/// `legal` is never empty.
+pub trait Policy {
+    fn choose(&mut self, board: &Board, legal: &[Direction]) -> Direction;
+}
+
+impl<F: FnMut(&Board, &[Direction]) -> Direction> Policy for F {
+    fn choose(&mut self, board: &Board, legal: &[Direction]) -> Direction {
+        self(board, legal)
+    }
+}
+
+/// Plays a uniformly random legal move.
+pub struct RandomPolicy<R>(pub R);
+
+impl<R: Rng> Policy for RandomPolicy<R> {
+    fn choose(&mut self, _board: &Board, legal: &[Direction]) -> Direction {
+        *legal.choose(&mut self.0).expect("policy called with a legal move")
+    }
+}
+
+/// Plays the expectimax choice on 4x4 boards and the first legal move otherwise.
+impl<H: Heuristic> Policy for Expectimax<H> {
+    fn choose(&mut self, board: &Board, legal: &[Direction]) -> Direction {
+        Bitboard::from_board(board)
+            .and_then(|packed| self.best_move(packed))
+            .map_or(legal[0], |best| best.direction)
+    }
+}
+
+/// Outcome of one game played to the end.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GameResult {
+    pub score: u64,
+    pub max_tile: u32,
+    pub moves: usize,
+    /// Address of the starting board and of every board after a spawn, in order,
+    /// so there are `moves + 1`. None for a board outside the library, e.g. one
+    /// holding two 2048s.
+    pub addresses: Vec<Option<BoardAddress>>,
+    pub final_board: Board,
+}
+
+/// Plays from `start` until no move changes the board: each turn the policy
+/// moves with `move_board` and `spawns` places a tile, e.g. a seeded
+/// `SpawnModel` or an `Adversary`.
+pub fn play_game(start: &Board, policy: &mut impl Policy, spawns: &mut impl SpawnPolicy) -> GameResult {
+    let mut board = start.clone();
+    let mut score = 0u64;
+    let mut moves = 0;
+    let mut addresses = vec![BoardAddress::from_board(&board)];
+    loop {
+        let legal = legal_moves(&board);
+        if legal.is_empty() {
+            break;
+        }
+        let (moved, points) = move_board_scored(&board, policy.choose(&board, &legal));
+        score += points as u64;
+        moves += 1;
+        board = match spawns.spawn(&moved) {
+            Some(spawn) => spawn.apply_to_board(&moved),
+            None => moved,
+        };
+        addresses.push(BoardAddress::from_board(&board));
+    }
+    let max_tile = board.rows().iter().flatten().copied().max().unwrap_or(0);
+    GameResult { score, max_tile, moves, addresses, final_board: board }
+}
+
+/// Plays `games` games from `start` in turn with the same policy and spawns.
+pub fn play_games(start: &Board, games: usize, policy: &mut impl Policy, spawns: &mut impl SpawnPolicy) -> Vec<GameResult> {
+    (0..games).map(|_| play_game(start, policy, spawns)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spawn::{Spawn, SpawnModel};
+
+    #[test]
+    fn every_board_has_an_address_slot() {
+        let start = Board::from_rows(vec![vec![2, 0, 0], vec![0, 0, 0], vec![0, 0, 2]]);
+        let mut policy = |_: &Board, legal: &[Direction]| legal[0];
+        let result = play_game(&start, &mut policy, &mut SpawnModel::classic(3));
+        assert_eq!(result.addresses.len(), result.moves + 1);
+        assert_eq!(result.addresses[0], BoardAddress::from_board(&start));
+        assert_eq!(result.addresses.last(), Some(&BoardAddress::from_board(&result.final_board)));
+    }
+
+    #[test]
+    fn boards_outside_the_library_keep_their_slot() {
+        // Left merges the 1024s into a second 2048, and a 64 then fills the board
+        // with no move left.
+        let start = Board::from_rows(vec![vec![1024, 1024, 32], vec![4, 2048, 8]]);
+        let mut policy = |_: &Board, legal: &[Direction]| legal[0];
+        let mut sixty_fours = |board: &Board| board.empty_cells().first().map(|&(row, col)| Spawn { row, col, exponent: 6 });
+        let result = play_game(&start, &mut policy, &mut sixty_fours);
+        assert_eq!(result.moves, 1);
+        assert_eq!(result.addresses.len(), 2);
+        assert!(result.addresses[0].is_some());
+        assert_eq!(result.addresses[1], None);
+    }
+}
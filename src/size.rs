@@ -0,0 +1,60 @@
+use std::fmt;
+use std::str::FromStr;
+
+/// Smallest and largest number of rows or columns a board can have. 5x5 is
+/// the largest size whose library index still fits in a u128 in either alphabet.
+pub const MIN_SIDE: usize = 2;
+pub const MAX_SIDE: usize = 5;
+
+/// Board dimensions. Cells are numbered row by row, `row * cols + col`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct BoardSize {
+    pub rows: usize,
+    pub cols: usize,
+}
+
+impl BoardSize {
+    /// The classic 4x4 game.
+    pub const CLASSIC: BoardSize = BoardSize { rows: 4, cols: 4 };
+
+    pub fn new(rows: usize, cols: usize) -> Option<Self> {
+        let side = MIN_SIDE..=MAX_SIDE;
+        (side.contains(&rows) && side.contains(&cols)).then_some(BoardSize { rows, cols })
+    }
+
+    /// Every supported size, row count first.
+    pub fn all() -> Vec<BoardSize> {
+        (MIN_SIDE..=MAX_SIDE)
+            .flat_map(|rows| (MIN_SIDE..=MAX_SIDE).map(move |cols| BoardSize { rows, cols }))
+            .collect()
+    }
+
+    pub fn cells(self) -> usize {
+        self.rows * self.cols
+    }
+}
+
+impl Default for BoardSize {
+    fn default() -> Self {
+        BoardSize::CLASSIC
+    }
+}
+
+impl fmt::Display for BoardSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}x{}", self.rows, self.cols)
+    }
+}
+
+impl FromStr for BoardSize {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (rows, cols) = s.trim().split_once(['x', 'X']).ok_or_else(|| format!("Invalid board size '{}'! Expected RxC, e.g. 3x3", s))?;
+        let parse = |v: &str| v.trim().parse::<usize>().map_err(|_| format!("Invalid board size '{}'! Non-integer side.", s));
+        let (rows, cols) = (parse(rows)?, parse(cols)?);
+        BoardSize::new(rows, cols).ok_or_else(|| {
+            format!("Invalid board size {}x{}! Sides must be in {}..={}", rows, cols, MIN_SIDE, MAX_SIDE)
+        })
+    }
+}
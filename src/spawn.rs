@@ -0,0 +1,252 @@
+use std::fmt;
+
+use rand::rngs::StdRng;
+use rand::seq::IndexedRandom;
+use rand::{Rng, SeedableRng};
+
+use crate::bitboard::Bitboard;
+use crate::board::Board;
+use crate::local_id::MAX_EXPONENT;
+
+/// Chance that a spawned tile is a 2; otherwise it is a 4.
+pub const TWO_PROBABILITY: f64 = 0.9;
+
+/// A tile placed on an empty cell after a move: exponent 1 for a 2, 2 for a 4, and so on.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Spawn {
+    pub row: usize,
+    pub col: usize,
+    pub exponent: u32,
+}
+
+impl Spawn {
+    pub fn value(self) -> u32 {
+        1 << self.exponent
+    }
+
+    /// Chance of this exact spawn under the classic rule when it is placed
+    /// uniformly among `empty` empty cells.
+    pub fn probability(self, empty: usize) -> f64 {
+        let tile = if self.exponent == 1 { TWO_PROBABILITY } else { 1.0 - TWO_PROBABILITY };
+        tile / empty as f64
+    }
+
+    /// Every spawn that can land on `board`: a 2 and a 4 on each empty cell.
+    pub fn all_on(board: &Board) -> Vec<Spawn> {
+        board
+            .empty_cells()
+            .into_iter()
+            .flat_map(|(row, col)| [1, 2].map(|exponent| Spawn { row, col, exponent }))
+            .collect()
+    }
+
+    pub fn apply_to_board(self, board: &Board) -> Board {
+        debug_assert_eq!(board[self.row][self.col], 0, "spawn on an occupied cell");
+        let mut spawned = board.clone();
+        spawned[self.row][self.col] = self.value();
+        spawned
+    }
+
+    pub fn apply(self, board: Bitboard) -> Bitboard {
+        debug_assert_eq!(board.get(self.row, self.col), 0, "spawn on an occupied cell");
+        let mut spawned = board;
+        spawned.set(self.row, self.col, self.exponent);
+        spawned
+    }
+}
+
+/// Chooses the tile that spawns on an afterstate, or None if it is full.
+pub trait SpawnPolicy {
+    fn spawn(&mut self, afterstate: &Board) -> Option<Spawn>;
+}
+
+impl<F: FnMut(&Board) -> Option<Spawn>> SpawnPolicy for F {
+    fn spawn(&mut self, afterstate: &Board) -> Option<Spawn> {
+        self(afterstate)
+    }
+}
+
+/// How tiles spawn after a move: on a uniformly random empty cell, with the tile
+/// drawn from a fixed distribution by a generator with an explicit seed, so the
+/// same seed and moves always give the same game. Only play draws from it: the
+/// searches and solvers (`successors`, `Expectimax`, `Adversary`,
+/// `RetrogradeTable`) always assume the classic rule.
+#[derive(Clone, Debug)]
+pub struct SpawnModel {
+    tiles: Vec<(u32, f64)>,
+    seed: u64,
+    rng: StdRng,
+}
+
+impl SpawnModel {
+    /// A model spawning each tile exponent with the given weight. Weights are
+    /// normalised; tiles of weight zero never spawn.
+    pub fn new(weights: &[(u32, f64)], seed: u64) -> Result<Self, String> {
+        let mut tiles: Vec<(u32, f64)> = Vec::new();
+        for &(exponent, weight) in weights {
+            if !(1..=MAX_EXPONENT).contains(&exponent) {
+                return Err(format!("Spawned tile must be between 2 and {}", 1u32 << MAX_EXPONENT));
+            }
+            if !weight.is_finite() || weight < 0.0 {
+                return Err(format!("Invalid weight {} for tile {}", weight, 1u32 << exponent));
+            }
+            if tiles.iter().any(|&(e, _)| e == exponent) {
+                return Err(format!("Tile {} is listed twice", 1u32 << exponent));
+            }
+            if weight > 0.0 {
+                tiles.push((exponent, weight));
+            }
+        }
+        let total: f64 = tiles.iter().map(|&(_, weight)| weight).sum();
+        if total <= 0.0 {
+            return Err("At least one tile needs a positive weight".to_string());
+        }
+        for (_, weight) in &mut tiles {
+            *weight /= total;
+        }
+        tiles.sort_by_key(|&(exponent, _)| exponent);
+        Ok(SpawnModel { tiles, seed, rng: StdRng::seed_from_u64(seed) })
+    }
+
+    /// The game's own rule: a 2 90% of the time, otherwise a 4.
+    pub fn classic(seed: u64) -> Self {
+        SpawnModel::new(&[(1, TWO_PROBABILITY), (2, 1.0 - TWO_PROBABILITY)], seed).expect("valid classic weights")
+    }
+
+    /// Only 4s spawn.
+    pub fn fours_only(seed: u64) -> Self {
+        SpawnModel::new(&[(2, 1.0)], seed).expect("valid weights")
+    }
+
+    /// Parses a distribution such as `2:0.9 4:0.1` (tile value, weight; separated
+    /// by spaces or commas).
+    pub fn parse(distribution: &str, seed: u64) -> Result<Self, String> {
+        let mut weights = Vec::new();
+        for entry in distribution.split([' ', ',']).filter(|entry| !entry.is_empty()) {
+            let malformed = || format!("Malformed entry '{}', expected tile:weight", entry);
+            let (value, weight) = entry.split_once(':').ok_or_else(malformed)?;
+            let value: u32 = value.parse().map_err(|_| malformed())?;
+            if value < 2 || !value.is_power_of_two() {
+                return Err(format!("Spawned tile must be a power of two, not {}", value));
+            }
+            let weight: f64 = weight.parse().map_err(|_| malformed())?;
+            weights.push((value.trailing_zeros(), weight));
+        }
+        SpawnModel::new(&weights, seed)
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Restarts the generator from `seed`.
+    pub fn reseed(&mut self, seed: u64) {
+        self.seed = seed;
+        self.rng = StdRng::seed_from_u64(seed);
+    }
+
+    /// Tile exponents that can spawn with their probabilities, smallest first.
+    pub fn tiles(&self) -> &[(u32, f64)] {
+        &self.tiles
+    }
+
+    /// Draws the next spawn on `board`, or None if it is full.
+    pub fn spawn(&mut self, board: &Board) -> Option<Spawn> {
+        self.pick(&board.empty_cells())
+    }
+
+    /// `spawn` for a packed 4x4 board.
+    pub fn spawn_packed(&mut self, board: Bitboard) -> Option<Spawn> {
+        let empty: Vec<(usize, usize)> = (0..4)
+            .flat_map(|row| (0..4).map(move |col| (row, col)))
+            .filter(|&(row, col)| board.get(row, col) == 0)
+            .collect();
+        self.pick(&empty)
+    }
+
+    fn pick(&mut self, empty: &[(usize, usize)]) -> Option<Spawn> {
+        let &(row, col) = empty.choose(&mut self.rng)?;
+        let mut roll: f64 = self.rng.random();
+        let mut exponent = self.tiles[self.tiles.len() - 1].0;
+        for &(tile, probability) in &self.tiles {
+            if roll < probability {
+                exponent = tile;
+                break;
+            }
+            roll -= probability;
+        }
+        Some(Spawn { row, col, exponent })
+    }
+}
+
+impl SpawnPolicy for SpawnModel {
+    fn spawn(&mut self, afterstate: &Board) -> Option<Spawn> {
+        SpawnModel::spawn(self, afterstate)
+    }
+}
+
+impl Default for SpawnModel {
+    fn default() -> Self {
+        SpawnModel::classic(0)
+    }
+}
+
+/// The distribution in the form `parse` reads, e.g. `2:0.9 4:0.1`.
+impl fmt::Display for SpawnModel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, &(exponent, probability)) in self.tiles.iter().enumerate() {
+            if i > 0 {
+                write!(f, " ")?;
+            }
+            let probability = format!("{:.6}", probability);
+            write!(f, "{}:{}", 1u32 << exponent, probability.trim_end_matches('0').trim_end_matches('.'))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::size::BoardSize;
+
+    fn draws(spawns: &mut SpawnModel) -> Vec<Spawn> {
+        let board = Board::empty(BoardSize::CLASSIC);
+        (0..50).map(|_| spawns.spawn(&board).expect("board has empty cells")).collect()
+    }
+
+    #[test]
+    fn same_seed_gives_same_spawns() {
+        let seed = u64::MAX - 12;
+        let first = draws(&mut SpawnModel::classic(seed));
+        assert_eq!(draws(&mut SpawnModel::classic(seed)), first);
+        assert_ne!(draws(&mut SpawnModel::classic(seed - 1)), first);
+        let mut reseeded = SpawnModel::classic(0);
+        draws(&mut reseeded);
+        reseeded.reseed(seed);
+        assert_eq!(draws(&mut reseeded), first);
+    }
+
+    #[test]
+    fn distributions_round_trip_through_display() {
+        for distribution in ["2:0.9 4:0.1", "4:1", "2:0.5 8:0.25 16:0.25"] {
+            let model = SpawnModel::parse(distribution, 1).unwrap();
+            assert_eq!(model.to_string(), distribution);
+            assert_eq!(SpawnModel::parse(&model.to_string(), 1).unwrap().tiles(), model.tiles());
+        }
+        assert_eq!(SpawnModel::parse("2:3, 4:1", 1).unwrap().to_string(), "2:0.75 4:0.25");
+    }
+
+    #[test]
+    fn bad_weights_are_rejected() {
+        assert!(SpawnModel::new(&[], 0).is_err());
+        assert!(SpawnModel::new(&[(1, 0.0)], 0).is_err());
+        assert!(SpawnModel::new(&[(1, -1.0), (2, 2.0)], 0).is_err());
+        assert!(SpawnModel::new(&[(1, f64::NAN)], 0).is_err());
+        assert!(SpawnModel::new(&[(1, 1.0), (1, 1.0)], 0).is_err());
+        assert!(SpawnModel::new(&[(0, 1.0)], 0).is_err());
+        assert!(SpawnModel::new(&[(MAX_EXPONENT + 1, 1.0)], 0).is_err());
+        assert!(SpawnModel::parse("3:1", 0).is_err());
+        assert!(SpawnModel::parse("2-1", 0).is_err());
+    }
+}
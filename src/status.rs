@@ -0,0 +1,48 @@
+use std::fmt;
+
+use crate::board::{Board, Direction, move_board};
+
+/// Tile that wins the classic game.
+pub const DEFAULT_GOAL: u32 = 2048;
+
+/// Whether a game on a board can go on, has been won, or is over.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum GameStatus {
+    Ongoing,
+    /// The board holds a tile of at least the goal; `tile` is its largest tile.
+    Won { tile: u32 },
+    /// No direction changes the board.
+    Lost,
+}
+
+impl GameStatus {
+    /// Status of `board` for reaching `goal`. A board with no legal move is lost
+    /// even if it holds the goal tile, since the game cannot continue.
+    pub fn of(board: &Board, goal: u32) -> Self {
+        if legal_moves(board).is_empty() {
+            return GameStatus::Lost;
+        }
+        let tile = board.rows().iter().flatten().copied().max().unwrap_or(0);
+        if tile >= goal { GameStatus::Won { tile } } else { GameStatus::Ongoing }
+    }
+}
+
+impl fmt::Display for GameStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GameStatus::Ongoing => write!(f, "Ongoing"),
+            GameStatus::Won { tile } => write!(f, "Won at {}", tile),
+            GameStatus::Lost => write!(f, "Lost"),
+        }
+    }
+}
+
+/// Whether moving `board` in `dir` changes it.
+pub fn is_legal(board: &Board, dir: Direction) -> bool {
+    move_board(board, dir) != *board
+}
+
+/// The directions that change `board`, in `Direction::ALL` order.
+pub fn legal_moves(board: &Board) -> Vec<Direction> {
+    Direction::ALL.into_iter().filter(|&dir| is_legal(board, dir)).collect()
+}
@@ -0,0 +1,86 @@
+use crate::address::BoardAddress;
+use crate::board::{Board, Direction, move_board};
+use crate::spawn::Spawn;
+
+/// A board reachable from an afterstate by one spawn, with its chance of occurring.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Outcome {
+    pub spawn: Spawn,
+    pub board: Board,
+    pub probability: f64,
+    /// None when the board is outside the library, e.g. it holds two 2048s.
+    pub address: Option<BoardAddress>,
+}
+
+/// The afterstate of one move and the distribution over the boards that follow it.
+/// The outcome probabilities sum to 1.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Successors {
+    pub direction: Direction,
+    pub afterstate: Board,
+    pub outcomes: Vec<Outcome>,
+}
+
+/// Every spawn on `afterstate` under the classic rule, whatever `SpawnModel` is
+/// in play: each empty cell receives a 2 with probability 0.9 and a 4 with
+/// probability 0.1, split evenly across the empty cells.
+pub fn spawn_outcomes(afterstate: &Board) -> Vec<Outcome> {
+    let empty = afterstate.empty_cells().len();
+    Spawn::all_on(afterstate)
+        .into_iter()
+        .map(|spawn| {
+            let board = spawn.apply_to_board(afterstate);
+            let address = BoardAddress::from_board(&board);
+            Outcome { spawn, board, probability: spawn.probability(empty), address }
+        })
+        .collect()
+}
+
+/// The stochastic successors of `board` for each direction that changes it.
+/// Directions that leave the board unchanged are not moves and are omitted.
+pub fn successors(board: &Board) -> Vec<Successors> {
+    Direction::ALL
+        .into_iter()
+        .filter_map(|direction| {
+            let afterstate = move_board(board, direction);
+            if afterstate == *board {
+                return None;
+            }
+            let outcomes = spawn_outcomes(&afterstate);
+            Some(Successors { direction, afterstate, outcomes })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn outcome_probabilities_sum_to_one() {
+        let boards = [
+            Board::from_rows(vec![vec![2, 0, 0, 0], vec![0, 4, 0, 0], vec![0, 0, 0, 0], vec![0, 0, 0, 2]]),
+            Board::from_rows(vec![vec![2, 4, 8], vec![4, 8, 2], vec![0, 2, 4]]),
+            Board::from_rows(vec![vec![1024, 1024], vec![0, 2]]),
+        ];
+        for board in &boards {
+            for successor in successors(board) {
+                let total: f64 = successor.outcomes.iter().map(|outcome| outcome.probability).sum();
+                assert!((total - 1.0).abs() < 1e-12, "{:?} sums to {}", successor.direction, total);
+                for outcome in &successor.outcomes {
+                    assert_eq!(outcome.board, outcome.spawn.apply_to_board(&successor.afterstate));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn directions_that_change_nothing_are_left_out() {
+        // Packed into the top-left corner: only Right and Down move anything.
+        let board = Board::from_rows(vec![vec![2, 4, 0], vec![8, 0, 0], vec![0, 0, 0]]);
+        let directions: Vec<Direction> = successors(&board).iter().map(|successor| successor.direction).collect();
+        assert_eq!(directions, vec![Direction::Down, Direction::Right]);
+        let full = Board::from_rows(vec![vec![2, 4], vec![4, 2]]);
+        assert!(successors(&full).is_empty());
+    }
+}
@@ -0,0 +1,184 @@
+use std::fmt;
+
+use crate::address::BoardAddress;
+use crate::bitboard::Bitboard;
+use crate::move_table::reverse_row;
+
+/// The 8 rotations and reflections of the square (the dihedral group D4).
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Symmetry {
+    Identity,
+    Rotate90,
+    Rotate180,
+    Rotate270,
+    FlipHorizontal,
+    FlipVertical,
+    Transpose,
+    AntiTranspose,
+}
+
+impl Symmetry {
+    pub const ALL: [Symmetry; 8] = [
+        Symmetry::Identity,
+        Symmetry::Rotate90,
+        Symmetry::Rotate180,
+        Symmetry::Rotate270,
+        Symmetry::FlipHorizontal,
+        Symmetry::FlipVertical,
+        Symmetry::Transpose,
+        Symmetry::AntiTranspose,
+    ];
+
+    /// Image of a board under this transform; rotations are clockwise.
+    pub fn apply(self, board: Bitboard) -> Bitboard {
+        match self {
+            Symmetry::Identity => board,
+            Symmetry::Rotate90 => flip_horizontal(board.transpose()),
+            Symmetry::Rotate180 => flip_vertical(flip_horizontal(board)),
+            Symmetry::Rotate270 => flip_vertical(board.transpose()),
+            Symmetry::FlipHorizontal => flip_horizontal(board),
+            Symmetry::FlipVertical => flip_vertical(board),
+            Symmetry::Transpose => board.transpose(),
+            Symmetry::AntiTranspose => flip_vertical(flip_horizontal(board.transpose())),
+        }
+    }
+
+    pub fn inverse(self) -> Symmetry {
+        match self {
+            Symmetry::Rotate90 => Symmetry::Rotate270,
+            Symmetry::Rotate270 => Symmetry::Rotate90,
+            other => other,
+        }
+    }
+}
+
+impl fmt::Display for Symmetry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Symmetry::Identity => "identity",
+            Symmetry::Rotate90 => "rotate 90°",
+            Symmetry::Rotate180 => "rotate 180°",
+            Symmetry::Rotate270 => "rotate 270°",
+            Symmetry::FlipHorizontal => "flip horizontal",
+            Symmetry::FlipVertical => "flip vertical",
+            Symmetry::Transpose => "transpose",
+            Symmetry::AntiTranspose => "anti-transpose",
+        };
+        f.write_str(name)
+    }
+}
+
+// Mirrors columns: (row, col) -> (row, 3 - col).
+fn flip_horizontal(board: Bitboard) -> Bitboard {
+    let mut flipped = board;
+    for row in 0..4 {
+        flipped.set_row(row, reverse_row(board.row(row)));
+    }
+    flipped
+}
+
+// Mirrors rows: (row, col) -> (3 - row, col).
+fn flip_vertical(board: Bitboard) -> Bitboard {
+    let mut flipped = board;
+    for row in 0..4 {
+        flipped.set_row(3 - row, board.row(row));
+    }
+    flipped
+}
+
+/// Canonical representative of a board's D4 orbit: the image with the smallest library index.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Canonical {
+    pub board: Bitboard,
+    pub address: BoardAddress,
+    /// Transform taking the original board to `board`.
+    pub transform: Symmetry,
+    /// Number of distinct boards among the 8 images (1, 2, 4 or 8).
+    pub orbit_size: usize,
+}
+
+/// None if the board has fewer than 2 tiles or repeats a tile of 2048 or more.
+/// All images share t, so comparing (global ID, local ID digits) orders them
+/// exactly as their library indexes would, in either local ID alphabet.
+pub fn canonicalize(board: Bitboard) -> Option<Canonical> {
+    let mut best: Option<Canonical> = None;
+    let mut images = Vec::with_capacity(8);
+    for transform in Symmetry::ALL {
+        let image = transform.apply(board);
+        if !images.contains(&image) {
+            images.push(image);
+        }
+        let address = image.address()?;
+        let key = (address.global_id, address.local_id.tiles());
+        if best.as_ref().is_none_or(|b| key < (b.address.global_id, b.address.local_id.tiles())) {
+            best = Some(Canonical { board: image, address, transform, orbit_size: 0 });
+        }
+    }
+    best.map(|canonical| Canonical { orbit_size: images.len(), ..canonical })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Fixed LCG so the sample of boards is the same on every run.
+    fn sample_boards(count: usize) -> Vec<Bitboard> {
+        let mut state = 0xd4_u64;
+        (0..count)
+            .map(|_| {
+                let mut board = Bitboard::default();
+                for cell in 0..16 {
+                    state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+                    let exp = ((state >> 33) % 16) as u32;
+                    board.set(cell / 4, cell % 4, if exp > 10 { 0 } else { exp });
+                }
+                board
+            })
+            .collect()
+    }
+
+    fn board(tiles: &[(usize, usize, u32)]) -> Bitboard {
+        let mut board = Bitboard::default();
+        for &(row, col, exp) in tiles {
+            board.set(row, col, exp);
+        }
+        board
+    }
+
+    #[test]
+    fn transforms_compose_as_expected() {
+        for board in sample_boards(200) {
+            let mut rotated = board;
+            for _ in 0..4 {
+                rotated = Symmetry::Rotate90.apply(rotated);
+            }
+            assert_eq!(rotated, board);
+            for transform in Symmetry::ALL {
+                assert_eq!(transform.inverse().apply(transform.apply(board)), board, "{}", transform);
+            }
+        }
+    }
+
+    #[test]
+    fn every_image_has_the_same_canonical_form() {
+        for board in sample_boards(200).into_iter().filter(|board| board.address().is_some()) {
+            let canonical = canonicalize(board).expect("board has an address");
+            assert_eq!(canonical.transform.apply(board), canonical.board);
+            for transform in Symmetry::ALL {
+                let image = canonicalize(transform.apply(board)).expect("images share the address's validity");
+                assert_eq!((image.board, image.orbit_size), (canonical.board, canonical.orbit_size), "{}", transform);
+            }
+        }
+    }
+
+    #[test]
+    fn orbit_sizes() {
+        let corners = board(&[(0, 0, 1), (0, 3, 1), (3, 0, 1), (3, 3, 1)]);
+        let diagonal = board(&[(0, 0, 1), (3, 3, 1)]);
+        let transposable = board(&[(0, 0, 1), (1, 1, 2)]);
+        let asymmetric = board(&[(0, 0, 1), (0, 1, 2)]);
+        for (board, size) in [(corners, 1), (diagonal, 2), (transposable, 4), (asymmetric, 8)] {
+            assert_eq!(canonicalize(board).expect("board has an address").orbit_size, size, "{}", board);
+        }
+    }
+}